@@ -1,39 +1,214 @@
 pub mod board;
+pub mod clock;
+mod move_input;
+mod move_list;
+mod pgn;
+pub mod scene;
+mod settings;
+pub mod theme;
+mod ui;
+pub mod viewport;
 
 use std::fmt::Display;
 
-use board::{Board, GridPosition, piece::PieceColor, sprites::SpritesMap};
+use board::{
+    Board, GridPosition,
+    annotation::MarkColor,
+    fen::{CastlingNotation, Position},
+    highlight::HighlightLayers,
+    orientation::Orientation,
+    piece::{PieceColor, PieceKind},
+    sprites::SpritesMap,
+    variant::Variant,
+};
+use clock::{Clock, TimeControl};
 use macroquad::{
     color::*,
-    input::{MouseButton, is_mouse_button_pressed},
-    math::Vec2,
-    shapes::draw_circle,
+    input::{
+        KeyCode, MouseButton, is_key_down, is_key_pressed, is_mouse_button_down,
+        is_mouse_button_pressed, is_mouse_button_released, mouse_wheel,
+    },
+    math::{Rect, Vec2},
+    shapes::{draw_circle, draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
     texture::Texture2D,
+    time::get_frame_time,
 };
+use move_input::{MoveInput, TypedAction};
+use move_list::MoveList;
+use scene::{Assets, Scene, Transition, pause::PauseOverlay, results::ResultsOverlay};
+use settings::SettingsMenu;
+use theme::Themes;
 use tracing::{info, warn};
 
+/// Everything a game is started from. Every side is played at this device,
+/// hot-seat.
+#[derive(Clone, Debug)]
+pub struct GameSetup {
+    pub variant: Variant,
+    /// Position to start from instead of the variant's starting position.
+    pub position: Option<Position>,
+    /// Clock to play with, untimed when `None`.
+    pub time_control: Option<TimeControl>,
+    /// Side shown at the bottom of the board.
+    pub color: PieceColor,
+}
+impl GameSetup {
+    /// The same game, with the next side at the bottom of the board.
+    pub fn rematch(&self) -> Self {
+        let colors = self.variant.colors();
+        let idx = colors.iter().position(|c| *c == self.color).unwrap_or(0);
+        Self {
+            color: colors[(idx + 1) % colors.len()],
+            ..self.clone()
+        }
+    }
+}
+
 pub struct Game {
+    setup: GameSetup,
     board: Board,
+    /// Side played at this device, which hot-seat is the side to move.
     player_color: PieceColor,
+    /// Side the device has to be handed to before the board is shown again,
+    /// so that hot-seat players don't see each other's view in fog of war.
+    handoff: Option<PieceColor>,
+    /// The selected piece is held with the mouse button, to be dropped on
+    /// release. Clicking a piece then its target works as well.
+    dragging: bool,
+    /// Turn the board so that the local player's side is at the bottom.
+    auto_orientation: bool,
+    /// Flips and rotations asked for by the player, on top of the automatic
+    /// orientation.
+    view_turn: Orientation,
+    /// The board after each move, starting with the initial position.
+    history: Vec<Board>,
+    /// Index in `history` of the position shown instead of the live one.
+    browsing: Option<usize>,
+    move_list: MoveList,
+    clock: Option<Clock>,
+    /// Square a right-drag started on, drawing an arrow or circling the
+    /// square once released.
+    annotating_from: Option<GridPosition>,
+    highlights: HighlightLayers,
+    settings: SettingsMenu,
+    /// Square picked with the arrow keys, acted on with Enter as if clicked.
+    /// Hidden until Enter is first pressed.
+    cursor: Option<GridPosition>,
+    move_input: MoveInput,
 
     pub ctx: Context,
 }
-
 impl Game {
-    pub fn new(
-        white_sprites: SpritesMap,
-        black_sprites: SpritesMap,
-        move_sprite: Texture2D,
-    ) -> Self {
-        Self {
-            board: Board::new(white_sprites, black_sprites, move_sprite),
-            player_color: PieceColor::White,
+    /// Starts a game as `setup` describes it, drawn with the themes in use.
+    pub fn new(setup: GameSetup, themes: &Themes, move_sprite: Texture2D) -> Self {
+        let variant = setup.variant;
+        info!("New {variant:?} game, {:?} at the bottom", setup.color);
+        // Sized for the board once it is set up, see `Game::apply_theme`.
+        let (white_sprites, black_sprites) = themes.piece_set().sprites(SpritesMap::TILE_SIZE);
+        let board = match setup.position.clone() {
+            Some(position) => {
+                Board::from_position(variant, position, white_sprites, black_sprites, move_sprite)
+            }
+            None => Board::new(variant, white_sprites, black_sprites, move_sprite),
+        };
+        let mut game = Self {
+            player_color: board.turn(),
+            handoff: None,
+            dragging: false,
+            auto_orientation: variant.has_fog(),
+            // The automatic orientation follows the side to move instead.
+            view_turn: if variant.has_fog() {
+                Orientation::default()
+            } else {
+                Orientation::of(setup.color)
+            },
+            history: vec![board.frozen()],
+            browsing: None,
+            move_list: MoveList::new(Rect {
+                x: 1080.0,
+                y: 280.0,
+                w: 600.0,
+                h: 620.0,
+            }),
+            clock: setup
+                .time_control
+                .clone()
+                .map(|control| Clock::new(control, variant.colors())),
+            annotating_from: None,
+            highlights: HighlightLayers::default(),
+            settings: SettingsMenu::new(
+                Rect {
+                    x: 1700.0,
+                    y: 200.0,
+                    w: 200.0,
+                    h: 56.0,
+                },
+                Rect {
+                    x: 1080.0,
+                    y: 280.0,
+                    w: 600.0,
+                    h: 384.0,
+                },
+            ),
+            cursor: None,
+            move_input: MoveInput::new(Rect {
+                x: 1080.0,
+                y: 920.0,
+                w: 600.0,
+                h: 56.0,
+            }),
+            board,
+            setup,
             ctx: Default::default(),
-        }
+        };
+        game.apply_theme(themes);
+        game.orient_board();
+        game
     }
 
-    pub fn draw(&self) {
-        self.board.render();
+    pub fn draw(&self, themes: &Themes) {
+        if let Some(color) = self.handoff {
+            draw_rectangle(0.0, 0.0, 1920.0, 1080.0, BLACK);
+            draw_text(
+                &format!("Pass the device to {color:?}, then click to continue"),
+                460.0,
+                540.0,
+                48.0,
+                WHITE,
+            );
+            return;
+        }
+        self.shown_board().render(&self.highlights);
+        if let Some(cursor) = self.cursor {
+            let Vec2 { x, y } = self.shown_board().world_from_grid(cursor);
+            let cell = self.shown_board().cell_size();
+            draw_rectangle_lines(x, y, cell.x, cell.y, 6.0, YELLOW);
+        }
+        self.move_input.draw();
+        if let Some(result) = self.board.result() {
+            draw_text(&result.to_string(), 1080.0, 250.0, 48.0, WHITE);
+        }
+        let moves = self.board.moves();
+        let current = match self.browsing {
+            Some(idx) => idx.checked_sub(1),
+            None => moves.len().checked_sub(1),
+        };
+        self.move_list
+            .draw(moves, self.board.variant().colors(), current);
+        if let Some(clock) = &self.clock {
+            let running = self.board.result().is_none().then(|| self.board.viewer());
+            clock.draw(
+                Rect {
+                    x: 1700.0,
+                    y: 280.0,
+                    w: 200.0,
+                    h: 620.0,
+                },
+                running,
+            );
+        }
+        self.settings.draw(&self.highlights, themes);
         draw_circle(
             self.ctx.mouse_position.x,
             self.ctx.mouse_position.y,
@@ -41,63 +216,490 @@ impl Game {
             RED,
         );
     }
-    pub fn update(&mut self) {
-        if let Some(p) = self.board.grid_from_world(self.ctx.mouse_position) {
-            if is_mouse_button_pressed(MouseButton::Left) {
-                let action = match (self.board.selected_piece_pos(), self.board.piece_at(p)) {
-                    (None, None) => ClickAction::Nothing,
-                    (None, Some(p)) => ClickAction::SelectNew(p.position),
-                    (Some(selected), None) => {
-                        let color = self.board.selected_piece().unwrap().color;
-                        if color == self.player_color {
-                            ClickAction::TryMove {
-                                from: selected,
-                                to: p,
-                            }
-                        } else {
-                            ClickAction::Nothing
-                        }
+    pub fn update(&mut self, themes: &mut Themes) {
+        self.board
+            .advance_animations(get_frame_time(), self.ctx.animation_duration);
+        self.run_clock();
+        let typing = self.move_input.is_open();
+        self.type_move();
+        if !typing {
+            self.shortcuts();
+        }
+        if self.cursor.is_some() {
+            self.move_cursor();
+        } else if !self.dragging && self.can_browse() && !self.move_input.is_open() {
+            self.navigate();
+        }
+        if !self.move_input.is_open()
+            && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter))
+        {
+            self.press_cursor();
+        }
+        if is_mouse_button_pressed(MouseButton::Right) && !self.settings.is_open() {
+            self.annotating_from = self.shown_board().grid_from_world(self.ctx.mouse_position);
+        }
+        if is_mouse_button_released(MouseButton::Right)
+            && let Some(from) = self.annotating_from.take()
+            && let Some(to) = self.shown_board().grid_from_world(self.ctx.mouse_position)
+        {
+            self.annotate(from, to);
+        }
+        if self.dragging {
+            if is_mouse_button_down(MouseButton::Left) {
+                if let Some(from) = self.board.selected_piece_pos() {
+                    self.board.drag(from, self.ctx.mouse_position);
+                }
+            } else {
+                self.drop_dragged();
+            }
+            return;
+        }
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        if self.handoff.take().is_some() {
+            return;
+        }
+        let theme = themes.settings();
+        if self.settings.click(
+            self.ctx.mouse_position,
+            &mut self.highlights,
+            themes,
+            self.board.variant().colors(),
+        ) {
+            if themes.settings() != theme {
+                self.apply_theme(themes);
+                themes.settings().save();
+            }
+            return;
+        }
+        if self.move_list.contains(self.ctx.mouse_position) {
+            if self.can_browse()
+                && let Some(idx) = self.move_list.move_at(
+                    self.ctx.mouse_position,
+                    self.board.moves(),
+                    self.board.variant().colors(),
+                )
+            {
+                self.browse(idx + 1);
+            }
+            return;
+        }
+        // The live game is left alone while a past position is shown.
+        if self.browsing.is_some() {
+            return;
+        }
+        let action = if let Some((color, kind)) = self.board.pocket_slot_at(self.ctx.mouse_position)
+        {
+            if color == self.player_color && self.board.pending_promotion().is_none() {
+                ClickAction::SelectDrop(kind)
+            } else {
+                ClickAction::Nothing
+            }
+        } else if let Some(p) = self.board.grid_from_world(self.ctx.mouse_position) {
+            match self.square_action(p) {
+                Some(action) => action,
+                None => return,
+            }
+        } else {
+            return;
+        };
+        self.dispatch(action);
+        // Own pieces can be picked up right away, whether just selected or not.
+        if let Some(piece) = self.board.selected_piece()
+            && piece.color == self.player_color
+            && self.board.grid_from_world(self.ctx.mouse_position) == Some(piece.position)
+        {
+            self.dragging = true;
+            self.board.drag(piece.position, self.ctx.mouse_position);
+        }
+    }
+    /// What clicking `p` on the live board does. A pending promotion is
+    /// resolved right away instead, leaving nothing else to do.
+    fn square_action(&mut self, p: GridPosition) -> Option<ClickAction> {
+        if self.board.pending_promotion().is_some() {
+            self.board.resolve_promotion(p);
+            if self.board.pending_promotion().is_none() && !self.board.duck_pending() {
+                self.end_turn();
+            }
+            return None;
+        }
+        if self.board.duck_pending() {
+            Some(ClickAction::PlaceDuck(p))
+        } else {
+            Some(self.board_click_action(p))
+        }
+    }
+    fn dispatch(&mut self, action: ClickAction) {
+        info!("{}", action);
+
+        // TODO: Would it be worth it to have Rc<RefCell> instead of loose references?
+        // Consider that the runtime costs of handling the references could be close
+        // to RefCell's assertions.
+        match action {
+            ClickAction::SelectNew(piece) => self.board.select_piece_at(piece),
+            ClickAction::TryMove { from, to } | ClickAction::TryCapture { from, to } => {
+                match self.board.try_move_piece(from, to) {
+                    Ok(()) if self.board.duck_pending() => (),
+                    Ok(()) => self.end_turn(),
+                    Err(err) => warn!("Invalid move: {:?}", err),
+                };
+            }
+            ClickAction::ChangeSelection { from: _, to } => self.board.select_piece_at(to),
+            ClickAction::SelectDrop(kind) => self.board.select_drop(kind),
+            ClickAction::TryDrop { kind, to } => match self.board.try_drop(kind, to) {
+                Ok(()) => self.end_turn(),
+                Err(err) => warn!("Invalid drop: {:?}", err),
+            },
+            ClickAction::PlaceDuck(to) => match self.board.try_place_duck(to) {
+                Ok(()) => self.end_turn(),
+                Err(err) => warn!("Invalid duck move: {:?}", err),
+            },
+            ClickAction::Nothing => (),
+        }
+    }
+    /// Shows the cursor on the first press of Enter, then acts on the square
+    /// under it as a click would.
+    fn press_cursor(&mut self) {
+        if self.handoff.take().is_some() {
+            return;
+        }
+        let Some(cursor) = self.cursor else {
+            // Starting from the selected piece or the own king saves steps.
+            let king = self
+                .board
+                .position()
+                .pieces
+                .into_iter()
+                .find(|p| p.kind == PieceKind::King && p.color == self.player_color)
+                .map(|p| p.position);
+            self.cursor = Some(self.board.selected_piece_pos().or(king).unwrap_or_default());
+            return;
+        };
+        if self.browsing.is_none()
+            && let Some(action) = self.square_action(cursor)
+        {
+            self.dispatch(action);
+        }
+    }
+    /// Moves the cursor with the arrow keys, towards where they point on
+    /// screen whichever way the board is turned.
+    fn move_cursor(&mut self) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        let step = if is_key_pressed(KeyCode::Left) {
+            Vec2 { x: -1.0, y: 0.0 }
+        } else if is_key_pressed(KeyCode::Right) {
+            Vec2 { x: 1.0, y: 0.0 }
+        } else if is_key_pressed(KeyCode::Up) {
+            Vec2 { x: 0.0, y: -1.0 }
+        } else if is_key_pressed(KeyCode::Down) {
+            Vec2 { x: 0.0, y: 1.0 }
+        } else {
+            return;
+        };
+        let board = self.shown_board();
+        let center = board.world_from_grid(cursor) + board.cell_size() * (step + 0.5);
+        if let Some(p) = board.grid_from_world(center) {
+            self.cursor = Some(p);
+        }
+    }
+    /// Opens the move box with the moves the local player can type.
+    fn open_move_input(&mut self) {
+        let moves = move_input::legal_moves(&self.board, self.player_color);
+        self.move_input.open(moves);
+    }
+    /// Plays the move entered in the move box, promoting to the piece typed
+    /// along with it or to a queen when none was. Runs every frame so that
+    /// keys typed with the box closed don't pile up.
+    fn type_move(&mut self) {
+        let Some((action, promotion)) = self.move_input.update() else {
+            return;
+        };
+        // The live game is left alone while a past position is shown.
+        if self.browsing.is_some() {
+            return;
+        }
+        let played = match action {
+            TypedAction::Move { from, to } => self.board.try_move_piece(from, to),
+            TypedAction::Drop { kind, to } => self.board.try_drop(kind, to),
+            TypedAction::Duck(to) => self.board.try_place_duck(to),
+        };
+        match played {
+            Ok(()) => {
+                self.board.choose_promotion_or_default(promotion);
+                if !self.board.duck_pending() {
+                    self.end_turn();
+                }
+                self.open_move_input();
+            }
+            Err(err) => warn!("Invalid move: {:?}", err),
+        }
+    }
+    /// Logs and view keys, along with Tab opening the move box.
+    fn shortcuts(&mut self) {
+        if is_key_pressed(KeyCode::F) && self.has_fen() {
+            let position = self.board.position();
+            info!("X-FEN: {}", position.to_fen(CastlingNotation::XFen));
+            info!(
+                "Shredder-FEN: {}",
+                position.to_fen(CastlingNotation::Shredder)
+            );
+        }
+        if is_key_pressed(KeyCode::P) && self.has_fen() {
+            info!("PGN:\n{}", pgn::write(&self.history, &self.board));
+        }
+        if is_key_pressed(KeyCode::X) {
+            self.view_turn = self.view_turn.flipped();
+            self.orient_board();
+        }
+        if is_key_pressed(KeyCode::R) {
+            self.view_turn = self.view_turn.rotated();
+            self.orient_board();
+        }
+        if is_key_pressed(KeyCode::O) {
+            self.auto_orientation = !self.auto_orientation;
+            self.orient_board();
+        }
+        if is_key_pressed(KeyCode::Tab) && self.handoff.is_none() {
+            self.open_move_input();
+        }
+    }
+    /// Tries to move the dragged piece to the square under the mouse. Dropping
+    /// it anywhere else, or on an illegal square, puts it back.
+    fn drop_dragged(&mut self) {
+        self.dragging = false;
+        let from = self.board.selected_piece_pos();
+        let to = self.board.grid_from_world(self.ctx.mouse_position);
+        if let (Some(from), Some(to)) = (from, to)
+            && from != to
+        {
+            info!("Drag {from}->{to}");
+            match self.board.try_move_piece(from, to) {
+                Ok(()) if self.board.duck_pending() => (),
+                Ok(()) => self.end_turn(),
+                Err(err) => warn!("Invalid move: {:?}", err),
+            }
+        }
+        self.board.release_drag();
+    }
+    /// Draws the live board and every past position with the current theme.
+    fn apply_theme(&mut self, themes: &Themes) {
+        let palette = themes.palette();
+        let (white, black) = themes
+            .piece_set()
+            .sprites(self.board.cell_size().min_element());
+        for board in self.history.iter_mut().chain([&mut self.board]) {
+            board.set_theme(palette, &white, &black);
+        }
+    }
+    /// Circles `from` when `to` is the same square and draws an arrow
+    /// otherwise, on the position shown. The color depends on the modifiers
+    /// held.
+    fn annotate(&mut self, from: GridPosition, to: GridPosition) {
+        let color = MarkColor::from_modifiers(
+            is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt),
+            is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl),
+        );
+        let board = match self.browsing {
+            Some(idx) => &mut self.history[idx],
+            None => &mut self.board,
+        };
+        let annotations = board.annotations_mut();
+        if from == to {
+            annotations.toggle_square(from, color);
+        } else {
+            annotations.toggle_arrow(from, to, color);
+        }
+        // Keep the history's copy of the live position in sync, unless it
+        // lags behind while a move is being completed.
+        if self.browsing.is_none() && self.history.len() == self.board.moves().len() + 1 {
+            let annotations = self.board.annotations().clone();
+            *self.history.last_mut().unwrap().annotations_mut() = annotations;
+        }
+    }
+    /// Hands the game over to the side to move once a move is complete.
+    fn end_turn(&mut self) {
+        if self.history.len() <= self.board.moves().len() {
+            // Marks belong to the position they were drawn on.
+            self.board.annotations_mut().clear();
+            let moves = self.board.moves();
+            if let Some(clock) = &mut self.clock {
+                clock.press(moves[moves.len() - 1].color);
+            }
+            self.history.push(self.board.frozen());
+            self.move_list
+                .reveal(moves.len() - 1, moves, self.board.variant().colors());
+        }
+        self.player_color = self.board.turn();
+        self.orient_board();
+        self.log_position();
+        if self.board.variant().has_fog()
+            && self.board.pending_promotion().is_none()
+            && self.board.result().is_none()
+        {
+            self.handoff = Some(self.player_color);
+        }
+        if self.move_input.is_open() {
+            self.open_move_input();
+        }
+    }
+    /// Runs the clock of the side to play, which is paused while the device
+    /// is handed over.
+    fn run_clock(&mut self) {
+        let Some(clock) = &mut self.clock else {
+            return;
+        };
+        if self.board.result().is_some() || self.handoff.is_some() {
+            return;
+        }
+        let color = self.board.viewer();
+        if clock.tick(color, get_frame_time()) {
+            info!("{color:?}'s flag fell");
+            self.board.flag_fall(color);
+            self.end_turn();
+        }
+    }
+    fn orient_board(&mut self) {
+        let base = if self.auto_orientation {
+            Orientation::of(self.player_color)
+        } else {
+            Orientation::default()
+        };
+        let orientation = base.then(self.view_turn);
+        self.board.set_orientation(orientation);
+        if let Some(idx) = self.browsing {
+            self.history[idx].set_orientation(orientation);
+        }
+    }
+    fn shown_board(&self) -> &Board {
+        self.browsing.map_or(&self.board, |idx| &self.history[idx])
+    }
+    /// Past positions are kept hidden while the fog would hide parts of them.
+    fn can_browse(&self) -> bool {
+        self.board.visible_squares().is_none()
+    }
+    /// Steps through the history with the arrow keys, up and down jumping to
+    /// either end, and scrolls the move list with the mouse wheel.
+    fn navigate(&mut self) {
+        let latest = self.history.len() - 1;
+        let shown = self.browsing.unwrap_or(latest);
+        if is_key_pressed(KeyCode::Left) {
+            self.browse(shown.saturating_sub(1));
+        } else if is_key_pressed(KeyCode::Right) {
+            self.browse((shown + 1).min(latest));
+        } else if is_key_pressed(KeyCode::Up) {
+            self.browse(0);
+        } else if is_key_pressed(KeyCode::Down) {
+            self.browse(latest);
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 && self.move_list.contains(self.ctx.mouse_position) {
+            self.move_list.scroll_by(
+                -wheel.signum() as isize,
+                self.board.moves(),
+                self.board.variant().colors(),
+            );
+        }
+    }
+    /// Shows the position after `idx` moves, going back to the live game
+    /// when it is the latest one.
+    fn browse(&mut self, idx: usize) {
+        self.browsing = (idx + 1 < self.history.len()).then_some(idx);
+        self.orient_board();
+        if let Some(ply) = idx.checked_sub(1) {
+            self.move_list
+                .reveal(ply, self.board.moves(), self.board.variant().colors());
+        }
+    }
+    /// FEN can only describe two-player games.
+    fn has_fen(&self) -> bool {
+        self.board.variant().colors().len() == 2
+    }
+    fn log_position(&self) {
+        if self.has_fen() {
+            info!("{}", self.board.position());
+        }
+    }
+    fn board_click_action(&self, p: GridPosition) -> ClickAction {
+        if let Some(kind) = self.board.selected_drop()
+            && self.board.piece_at(p).is_none()
+        {
+            return ClickAction::TryDrop { kind, to: p };
+        }
+        // Pieces hidden by the fog are treated as empty squares.
+        let target = self.board.piece_at(p).filter(|_| self.board.is_visible(p));
+        match (self.board.selected_piece_pos(), target) {
+            (None, None) => ClickAction::Nothing,
+            (None, Some(p)) => ClickAction::SelectNew(p.position),
+            (Some(selected), None) => {
+                let color = self.board.selected_piece().unwrap().color;
+                if color == self.player_color {
+                    ClickAction::TryMove {
+                        from: selected,
+                        to: p,
                     }
-                    (Some(from), Some(to)) => {
-                        let color = self.board.selected_piece().unwrap().color;
-                        if from == to.position {
-                            ClickAction::Nothing
-                        } else if color == self.player_color {
-                            if color == to.color {
-                                ClickAction::ChangeSelection {
-                                    from,
-                                    to: to.position,
-                                }
-                            } else {
-                                ClickAction::TryCapture {
-                                    from,
-                                    to: to.position,
-                                }
-                            }
-                        } else {
-                            ClickAction::SelectNew(to.position)
+                } else {
+                    ClickAction::Nothing
+                }
+            }
+            (Some(from), Some(to)) => {
+                let color = self.board.selected_piece().unwrap().color;
+                if from == to.position {
+                    ClickAction::Nothing
+                } else if color == self.player_color {
+                    // Own pieces can only be targeted when castling.
+                    if color == to.color && !self.board.legal_moves(from).contains(&to.position) {
+                        ClickAction::ChangeSelection {
+                            from,
+                            to: to.position,
+                        }
+                    } else {
+                        ClickAction::TryCapture {
+                            from,
+                            to: to.position,
                         }
                     }
-                };
-                info!("{}", action);
-
-                // TODO: Would it be worth it to have Rc<RefCell> instead of loose references?
-                // Consider that the runtime costs of handling the references could be close
-                // to RefCell's assertions.
-                match action {
-                    ClickAction::SelectNew(piece) => self.board.select_piece_at(piece),
-                    ClickAction::TryMove { from, to } | ClickAction::TryCapture { from, to } => {
-                        if let Err(err) = self.board.try_move_piece(from, to) {
-                            warn!("Invalid move: {:?}", err);
-                        };
-                    }
-                    ClickAction::ChangeSelection { from: _, to } => self.board.select_piece_at(to),
-                    ClickAction::Nothing => (),
+                } else {
+                    ClickAction::SelectNew(to.position)
                 }
             }
         }
     }
 }
+impl Scene for Game {
+    /// Pauses on Escape, unless it closes the settings menu, the move box or
+    /// the cursor, and announces the result once the game ends.
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition {
+        self.ctx.mouse_position = ctx.mouse_position;
+        if is_key_pressed(KeyCode::Escape) {
+            if self.settings.is_open() {
+                self.settings.toggle();
+            } else if self.move_input.is_open() {
+                self.move_input.close();
+            } else if self.cursor.is_some() {
+                self.cursor = None;
+            } else {
+                return Transition::Push(Box::new(PauseOverlay));
+            }
+        }
+        self.draw(&assets.themes);
+        let ongoing = self.board.result().is_none();
+        self.update(&mut assets.themes);
+        match self.board.result() {
+            Some(result) if ongoing => {
+                Transition::Push(Box::new(ResultsOverlay::new(result, self.setup.clone())))
+            }
+            _ => Transition::Stay,
+        }
+    }
+    fn draw_covered(&self, assets: &Assets) {
+        self.draw(&assets.themes);
+    }
+}
+
 #[derive(Debug)]
 pub enum ClickAction {
     SelectNew(GridPosition),
@@ -113,6 +715,13 @@ pub enum ClickAction {
         from: GridPosition,
         to: GridPosition,
     },
+    SelectDrop(PieceKind),
+    TryDrop {
+        kind: PieceKind,
+        to: GridPosition,
+    },
+    /// Second half of a Duck chess turn.
+    PlaceDuck(GridPosition),
     Nothing,
 }
 impl Display for ClickAction {
@@ -130,12 +739,28 @@ impl Display for ClickAction {
             ClickAction::TryCapture { from, to } => {
                 write!(f, "Capture {from}->{to}")
             }
+            ClickAction::SelectDrop(kind) => {
+                write!(f, "Select {kind:?} in pocket")
+            }
+            ClickAction::TryDrop { kind, to } => {
+                write!(f, "Drop {kind:?}@{to}")
+            }
+            ClickAction::PlaceDuck(to) => write!(f, "Duck@{to}"),
             ClickAction::Nothing => write!(f, "Nothing"),
         }
     }
 }
 
-#[derive(Default)]
 pub struct Context {
     pub mouse_position: Vec2,
+    /// Seconds a piece takes to slide to its square after a move.
+    pub animation_duration: f32,
+}
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            mouse_position: Vec2::ZERO,
+            animation_duration: 0.2,
+        }
+    }
 }
@@ -1,104 +1,533 @@
+pub mod annotation;
+pub mod castling;
+pub mod fen;
+pub mod highlight;
+pub mod orientation;
+pub mod palette;
 pub mod piece;
 pub mod player;
+pub mod rules;
+pub mod san;
+pub mod setup;
+pub mod shape;
+pub mod shogi;
 pub mod sprites;
+pub mod svg;
+pub mod variant;
 
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    rc::Rc,
+    str::FromStr,
+};
 
+use annotation::Annotations;
+use castling::{CastlingRights, CastlingSide};
+use fen::Position;
+use highlight::HighlightLayers;
 use macroquad::{
     color::*,
-    math::U16Vec2,
-    shapes::draw_rectangle,
+    math::{Rect, U16Vec2},
+    shapes::{draw_circle_lines, draw_line, draw_rectangle, draw_triangle},
     text::draw_text,
-    texture::{Texture2D, draw_texture},
+    texture::{DrawTextureParams, Texture2D, draw_texture_ex},
 };
+use orientation::Orientation;
+use palette::BoardPalette;
 use piece::{Piece, PieceColor, PieceKind};
 use player::Players;
+use rules::{GameResult, WinReason};
+use san::PlayedMove;
+use shape::{BoardShape, SquareKind};
 use sprites::SpritesMap;
+use variant::Variant;
 
 use super::Vec2;
 
+/// Side length in pixels of the area the board is drawn in.
+const BOARD_SIZE: f32 = 1024.0;
+const POCKET_MARGIN: f32 = 32.0;
+const POCKET_SLOT_SIZE: f32 = 96.0;
+/// Order in which pocket pieces are laid out.
+const POCKET_ORDER: [PieceKind; 7] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Archbishop,
+    PieceKind::Chancellor,
+    PieceKind::Queen,
+];
+
+#[derive(Clone)]
 pub struct Board {
+    variant: Variant,
     num_cells: U16Vec2,
+    shape: Rc<BoardShape>,
     cell_size: Vec2,
+    orientation: Orientation,
     selected_piece_pos: Option<GridPosition>,
+    /// Pocket piece of the side to move selected for dropping.
+    selected_drop: Option<PieceKind>,
     players: Players,
     move_sprite: Texture2D,
+    palette: BoardPalette,
+    turn: PieceColor,
+    castling: CastlingRights,
+    /// Square skipped by the last pawn double step.
+    en_passant: Option<GridPosition>,
+    halfmove_clock: u32,
+    fullmove: u32,
+    pending_promotion: Option<PendingPromotion>,
+    /// Square of the duck in Duck chess. The duck isn't part of FEN.
+    duck: Option<GridPosition>,
+    /// The side to move has moved a piece and still has to move the duck.
+    duck_pending: bool,
+    result: Option<GameResult>,
+    /// Origin and destination of the last move, without an origin for drops.
+    last_move: Option<(Option<GridPosition>, GridPosition)>,
+    /// Square of the piece being dragged, and the top left corner it is
+    /// drawn at.
+    dragged: Option<(GridPosition, Vec2)>,
+    animations: Vec<MoveAnimation>,
+    moves: Vec<PlayedMove>,
+    /// Move being played, listed once it is complete.
+    current_move: Option<PlayedMove>,
+    /// Marks drawn on the current position while analysing.
+    annotations: Annotations,
 }
 impl Board {
     pub fn new(
+        variant: Variant,
+        white_sprites: SpritesMap,
+        black_sprites: SpritesMap,
+        move_sprite: Texture2D,
+    ) -> Self {
+        Self::from_position(
+            variant,
+            variant.starting_position(),
+            white_sprites,
+            black_sprites,
+            move_sprite,
+        )
+    }
+    /// Sets up a board from an arbitrary position, such as one read from FEN.
+    /// `variant` only provides the rules, the position's dimensions are kept.
+    pub fn from_position(
+        variant: Variant,
+        position: Position,
         white_sprites: SpritesMap,
         black_sprites: SpritesMap,
         move_sprite: Texture2D,
     ) -> Self {
+        let num_cells = position.num_cells();
+        let cell = BOARD_SIZE / num_cells.max_element() as f32;
+        let mut players = Players::new(
+            variant.colors(),
+            position.pieces,
+            black_sprites,
+            white_sprites,
+        );
+        for (kind, color) in position.pockets {
+            players.add_to_pocket(color, kind);
+        }
         Self {
-            num_cells: U16Vec2 { x: 8, y: 8 },
-            cell_size: Vec2 { x: 128.0, y: 128.0 },
+            variant,
+            num_cells,
+            shape: Rc::new(position.shape),
+            cell_size: Vec2 { x: cell, y: cell },
+            orientation: Orientation::default(),
             selected_piece_pos: None,
-            players: Players::new(black_sprites, white_sprites),
+            selected_drop: None,
+            players,
             move_sprite,
+            palette: BoardPalette::default(),
+            turn: position.turn,
+            castling: position.castling,
+            en_passant: position.en_passant,
+            halfmove_clock: position.halfmove_clock,
+            fullmove: position.fullmove,
+            pending_promotion: None,
+            duck: None,
+            duck_pending: false,
+            result: None,
+            last_move: None,
+            dragged: None,
+            animations: vec![],
+            moves: vec![],
+            current_move: None,
+            annotations: Annotations::default(),
         }
     }
-
-    fn draw_pieces(&self) {
-        self.players
-            .draw(self.num_cells.y, self.cell_size, self.selected_piece_pos);
+    /// Copy of the board to show a past position with, leaving out the
+    /// selection, animations and move list.
+    pub fn frozen(&self) -> Self {
+        Self {
+            selected_piece_pos: None,
+            selected_drop: None,
+            dragged: None,
+            animations: vec![],
+            moves: vec![],
+            current_move: None,
+            ..self.clone()
+        }
+    }
+    /// The current position, as it would be written to FEN.
+    pub fn position(&self) -> Position {
+        Position {
+            shape: BoardShape::clone(&self.shape),
+            pieces: self.players.pieces().into_values().collect(),
+            pockets: [PieceColor::White, PieceColor::Black]
+                .into_iter()
+                .flat_map(|c| self.players.pocket(c).iter().map(move |k| (*k, c)))
+                .collect(),
+            turn: self.turn,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove: self.fullmove,
+        }
+    }
+    #[inline]
+    pub fn turn(&self) -> PieceColor {
+        self.turn
+    }
+    #[inline]
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+    #[inline]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+    #[inline]
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+    #[inline]
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+    /// Moves played so far, in order.
+    #[inline]
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves
+    }
+    /// Size in pixels of each square as drawn.
+    #[inline]
+    pub fn cell_size(&self) -> Vec2 {
+        self.cell_size
+    }
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+    /// Draws the board in `palette` and the pieces with the given sprites.
+    pub fn set_theme(
+        &mut self,
+        palette: &BoardPalette,
+        white_sprites: &SpritesMap,
+        black_sprites: &SpritesMap,
+    ) {
+        self.palette = palette.clone();
+        self.players.set_sprites(black_sprites, white_sprites);
     }
-    fn draw_gizmos(&self) {
-        if let Some(piece) = self
-            .selected_piece_pos
-            .and_then(|p| self.players.piece_at(p))
-        {
-            let moves = piece.pseudo_moveset(&self.snapshot());
-
-            for mov in moves {
-                let mut snapshot = self.snapshot();
-                let opposite = match piece.color {
-                    PieceColor::Black => PieceColor::White,
-                    PieceColor::White => PieceColor::Black,
-                };
-                snapshot.move_piece(piece.position, mov);
-                let atks = snapshot.attack_map(opposite);
 
-                if atks.contains(&self.players.king_position(piece.color))
-                    || (piece.kind == PieceKind::King && atks.contains(&mov))
-                {
+    fn draw_pieces(&self, visible: Option<&HashSet<GridPosition>>) {
+        let moving: Vec<GridPosition> = self
+            .animations
+            .iter()
+            .map(|a| a.square)
+            .chain(self.dragged.map(|(square, _)| square))
+            .collect();
+        self.players.draw(
+            |p| self.world_from_grid(p),
+            self.cell_size,
+            self.selected_piece_pos,
+            visible,
+            &moving,
+        );
+        for animation in &self.animations {
+            if visible.is_some_and(|v| !v.contains(&animation.square)) {
+                continue;
+            }
+            let end = self.world_from_grid(animation.square);
+            self.players.draw_piece_at(
+                animation.square,
+                animation.start.lerp(end, animation.eased()),
+                self.cell_size,
+                self.selected_piece_pos,
+            );
+        }
+    }
+    /// Drawn last, so that it stays above everything else on the board.
+    fn draw_dragged(&self) {
+        if let Some((square, world)) = self.dragged {
+            self.players
+                .draw_piece_at(square, world, self.cell_size, self.selected_piece_pos);
+        }
+    }
+    fn draw_duck(&self) {
+        let Some(duck) = self.duck else {
+            return;
+        };
+        self.players.sprites(PieceColor::White).draw_duck(
+            self.world_from_grid(duck),
+            self.cell_size,
+            YELLOW,
+        );
+    }
+    /// Covers the squares outside `visible`.
+    fn draw_fog(&self, visible: &HashSet<GridPosition>) {
+        for y in 0..self.num_cells.y {
+            for x in 0..self.num_cells.x {
+                let position = GridPosition { x, y };
+                if visible.contains(&position) || !self.shape.is_playable(position) {
                     continue;
                 }
-
-                let GridPosition { x, y } = mov;
-                let y = self.num_cells.y - y - 1;
-                draw_texture(
-                    &self.move_sprite,
-                    x as f32 * self.cell_size.x,
-                    y as f32 * self.cell_size.y,
-                    WHITE,
+                let world = self.world_from_grid(position);
+                draw_rectangle(
+                    world.x,
+                    world.y,
+                    self.cell_size.x,
+                    self.cell_size.y,
+                    Color {
+                        r: 0.15,
+                        g: 0.15,
+                        b: 0.2,
+                        a: 0.9,
+                    },
                 );
             }
-        };
+        }
     }
-    fn draw_attacks(&self) {
-        let mut moves = self.snapshot();
-        let moves = moves.attack_map(PieceColor::Black);
-
+    fn draw_gizmos(&self) {
+        if self.pending_promotion.is_some() {
+            return;
+        }
+        let moves = match (self.selected_piece_pos, self.selected_drop) {
+            _ if self.duck_pending => self.legal_duck_squares(),
+            (Some(from), _) => self.legal_moves(from),
+            (None, Some(kind)) => self.legal_drops(kind),
+            (None, None) => return,
+        };
         for mov in moves {
-            let GridPosition { x, y } = mov;
-            let y = self.num_cells.y - y - 1;
+            let world = self.world_from_grid(mov);
+            draw_texture_ex(
+                &self.move_sprite,
+                world.x,
+                world.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(self.cell_size),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    /// Circles and arrows drawn while analysing, between square centers.
+    fn draw_annotations(&self) {
+        let size = self.cell_size.min_element();
+        let center = |p| self.world_from_grid(p) + self.cell_size / 2.0;
+        for &(square, mark) in self.annotations.squares() {
+            let Vec2 { x, y } = center(square);
+            draw_circle_lines(x, y, size * 0.45, size * 0.07, mark.color());
+        }
+        for &(from, to, mark) in self.annotations.arrows() {
+            let (start, end) = (center(from), center(to));
+            let direction = (end - start).normalize_or_zero();
+            let normal = direction.perp();
+            let head = size * 0.4;
+            let base = end - direction * head;
+            draw_line(start.x, start.y, base.x, base.y, size * 0.15, mark.color());
+            draw_triangle(
+                end,
+                base + normal * head / 2.0,
+                base - normal * head / 2.0,
+                mark.color(),
+            );
+        }
+    }
+    fn draw_pockets(&self) {
+        for color in [PieceColor::Black, PieceColor::White] {
+            let sprites = self.players.sprites(color);
+            for (idx, (kind, count)) in self.pocket_slots(color).into_iter().enumerate() {
+                let rect = self.pocket_slot_rect(color, idx);
+                let modulate = if color == self.turn && self.selected_drop == Some(kind) {
+                    RED
+                } else {
+                    WHITE
+                };
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, DARKGRAY);
+                sprites.draw_piece(kind, rect.point(), rect.size(), modulate);
+                draw_text(
+                    &count.to_string(),
+                    rect.x + 4.0,
+                    rect.y + rect.h - 4.0,
+                    POCKET_SLOT_SIZE / 3.0,
+                    YELLOW,
+                );
+            }
+        }
+    }
+    fn draw_promotion(&self) {
+        let Some(pending) = &self.pending_promotion else {
+            return;
+        };
+        let sprites = self.players.sprites(pending.color);
+        for (idx, kind) in pending.choices.iter().enumerate() {
+            let Some(square) = pending.square_of(idx) else {
+                continue;
+            };
+            let position = self.world_from_grid(square);
             draw_rectangle(
-                *x as f32 * self.cell_size.x,
-                y as f32 * self.cell_size.y,
+                position.x,
+                position.y,
                 self.cell_size.x,
                 self.cell_size.y,
                 Color {
-                    r: 1.0,
-                    g: 0.5,
+                    r: 0.3,
+                    g: 0.3,
                     b: 0.3,
-                    a: 0.8,
+                    a: 0.9,
                 },
             );
+            sprites.draw_piece(*kind, position, self.cell_size, WHITE);
+        }
+    }
+    /// Drawn below the pieces so that they stay visible. In fog of war the
+    /// attack map and hanging pieces would give the enemy away, so only the
+    /// layers of squares the viewer sees are drawn.
+    fn draw_highlights(&self, layers: &HighlightLayers, visible: Option<&HashSet<GridPosition>>) {
+        let is_visible = |p: &GridPosition| visible.is_none_or(|v| v.contains(p));
+        if layers.last_move
+            && let Some((from, to)) = self.last_move
+        {
+            for square in from.into_iter().chain([to]).filter(is_visible) {
+                self.highlight_square(square, HighlightLayers::LAST_MOVE);
+            }
+        }
+        let mut state = self.snapshot();
+        if visible.is_none() {
+            if let Some(color) = layers.attacks {
+                let mut counts: HashMap<GridPosition, usize> = HashMap::new();
+                for square in state.attack_map(color) {
+                    *counts.entry(*square).or_default() += 1;
+                }
+                for (square, count) in counts {
+                    let mut shade = HighlightLayers::ATTACK;
+                    shade.a *= count.min(HighlightLayers::MAX_ATTACK_SHADES) as f32;
+                    self.highlight_square(square, shade);
+                }
+            }
+            if layers.hanging {
+                self.draw_hanging(&state);
+            }
+        }
+        if layers.check
+            && self.variant.rules().in_check(&mut state, self.turn)
+            && let Some(king) = state.king_position(self.turn).filter(is_visible)
+        {
+            self.highlight_square(king, HighlightLayers::CHECK);
+        }
+    }
+    /// Marks the corner of every piece other than a king that an enemy
+    /// attacks and its own side doesn't guard.
+    fn draw_hanging(&self, state: &BoardState) {
+        let attacks: HashMap<PieceColor, HashSet<GridPosition>> = self
+            .variant
+            .colors()
+            .iter()
+            .map(|&color| (color, state.attacked_by(color).into_iter().collect()))
+            .collect();
+        let hanging = state.pieces().filter(|p| {
+            p.kind != PieceKind::King
+                && !attacks[&p.color].contains(&p.position)
+                && state
+                    .enemies(p.color)
+                    .any(|enemy| attacks[&enemy].contains(&p.position))
+        });
+        for piece in hanging {
+            let world = self.world_from_grid(piece.position);
+            let corner = self.cell_size.min_element() * 0.3;
+            draw_triangle(
+                world,
+                world + Vec2::new(corner, 0.0),
+                world + Vec2::new(0.0, corner),
+                HighlightLayers::HANGING,
+            );
+        }
+    }
+
+    /// Draws the part of `texture` covering the square at `world`, the
+    /// texture being stretched over the whole board.
+    fn draw_texture_square(&self, texture: &Texture2D, world: Vec2) {
+        let scale = texture.size() / self.drawn_size();
+        draw_texture_ex(
+            texture,
+            world.x,
+            world.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(self.cell_size),
+                source: Some(Rect {
+                    x: world.x * scale.x,
+                    y: world.y * scale.y,
+                    w: self.cell_size.x * scale.x,
+                    h: self.cell_size.y * scale.y,
+                }),
+                ..Default::default()
+            },
+        );
+    }
+    /// Marks the edges joined by the board's topology.
+    fn draw_wrap_hint(&self) {
+        const THICKNESS: f32 = 6.0;
+        let color = Color {
+            r: 0.3,
+            g: 0.6,
+            b: 1.0,
+            a: 0.7,
+        };
+        let Vec2 {
+            x: width,
+            y: height,
+        } = self.drawn_size();
+        let topology = self.shape.topology();
+        let (vertical_edges, horizontal_edges) = if self.orientation.is_sideways() {
+            (topology.wraps_ranks(), topology.wraps_files())
+        } else {
+            (topology.wraps_files(), topology.wraps_ranks())
+        };
+        if vertical_edges {
+            draw_rectangle(0.0, 0.0, THICKNESS, height, color);
+            draw_rectangle(width - THICKNESS, 0.0, THICKNESS, height, color);
+        }
+        if horizontal_edges {
+            draw_rectangle(0.0, 0.0, width, THICKNESS, color);
+            draw_rectangle(0.0, height - THICKNESS, width, THICKNESS, color);
+        }
+    }
+    /// Scores of a game with more than two sides, drawn to the right of the board.
+    fn draw_scores(&self) {
+        for (idx, &color) in self.variant.colors().iter().enumerate() {
+            let mut line = format!("{color:?}: {}", self.players.score(color));
+            if self.players.is_eliminated(color) {
+                line.push_str(" (out)");
+            } else if color == self.turn {
+                line.push_str(" <");
+            }
+            draw_text(
+                &line,
+                BOARD_SIZE + POCKET_MARGIN,
+                POCKET_MARGIN + 40.0 * (idx + 1) as f32,
+                40.0,
+                WHITE,
+            );
         }
     }
 
-    pub fn render(&self) {
+    pub fn render(&self, layers: &HighlightLayers) {
         let Vec2 { x: w, y: h } = self.cell_size;
         let U16Vec2 {
             x: rows,
@@ -108,49 +537,165 @@ impl Board {
         // Have both draw calls separated
         for y in 0..columns {
             for x in 0..rows {
-                let inverted_y = (columns - 1) - y;
-                let color = if (x + inverted_y) % 2 == 0 {
-                    BLACK
-                } else {
-                    WHITE
+                let position = GridPosition { x, y };
+                let world = self.world_from_grid(position);
+                let color = match self.shape.kind(position) {
+                    Some(SquareKind::Normal) => match &self.palette.texture {
+                        Some(texture) => {
+                            self.draw_texture_square(texture, world);
+                            continue;
+                        }
+                        None => self.palette.square(x, y),
+                    },
+                    Some(SquareKind::Wall) => self.palette.wall,
+                    Some(SquareKind::Hole) | None => continue,
                 };
-                let (mapped_x, mapped_y) = (x as f32 * w, y as f32 * h);
-                draw_rectangle(mapped_x, mapped_y, w, h, color);
+                draw_rectangle(world.x, world.y, w, h, color);
             }
         }
         for y in 0..columns {
             for x in 0..rows {
-                let inverted_y = (columns - 1) - y;
-                let color = if (x + inverted_y) % 2 == 0 {
-                    WHITE
-                } else {
-                    BLACK
-                };
-                let (mapped_x, mapped_y) = (x as f32 * w, y as f32 * h);
+                let position = GridPosition { x, y };
+                if !self.shape.is_playable(position) {
+                    continue;
+                }
+                let color = self.palette.label(x, y);
+                let world = self.world_from_grid(position);
                 draw_text(
-                    &format!("{}", GridPosition { x, y: inverted_y }),
-                    mapped_x,
-                    mapped_y + self.cell_size.y,
-                    32.0,
+                    &format!("{position}"),
+                    world.x,
+                    world.y + h,
+                    32.0 * h / SpritesMap::TILE_SIZE,
                     color,
                 );
             }
         }
-        self.draw_pieces();
+        self.draw_wrap_hint();
+        let visible = self.visible_squares();
+        self.draw_highlights(layers, visible.as_ref());
+        self.draw_pieces(visible.as_ref());
+        self.draw_duck();
+        if let Some(visible) = &visible {
+            self.draw_fog(visible);
+        }
         self.draw_gizmos();
-        self.draw_attacks();
+        self.draw_annotations();
+        self.draw_dragged();
+        self.draw_promotion();
+        if self.variant.has_drops() {
+            self.draw_pockets();
+        }
+        if self.variant.colors().len() > 2 {
+            self.draw_scores();
+        }
+    }
+
+    /// Side whose view of the board is shown, the one about to play.
+    pub fn viewer(&self) -> PieceColor {
+        self.pending_promotion
+            .as_ref()
+            .map_or(self.turn, |pending| pending.color)
+    }
+    /// Squares the viewer can see, `None` when the whole board is visible
+    /// either because the variant has no fog or because the game is over.
+    pub fn visible_squares(&self) -> Option<HashSet<GridPosition>> {
+        (self.variant.has_fog() && self.result.is_none())
+            .then(|| self.snapshot().visible_to(self.viewer()))
+    }
+    /// Whether the contents of `position` are shown to the viewer.
+    pub fn is_visible(&self, position: GridPosition) -> bool {
+        self.visible_squares()
+            .is_none_or(|visible| visible.contains(&position))
     }
 
+    /// Covers the square at `p` with `color`, for marks drawn on top of the
+    /// board.
+    pub fn highlight_square(&self, p: GridPosition, color: Color) {
+        let world = self.world_from_grid(p);
+        draw_rectangle(world.x, world.y, self.cell_size.x, self.cell_size.y, color);
+    }
+    /// Size of the area the squares are drawn in, turned with the board.
+    fn drawn_size(&self) -> Vec2 {
+        self.orientation.screen_cells(self.num_cells).as_vec2() * self.cell_size
+    }
+    /// Square drawn at `pos`, inverse of [`Board::world_from_grid`].
     pub fn grid_from_world(&self, pos: Vec2) -> Option<GridPosition> {
-        let height = self.cell_size.y * self.num_cells.y as f32;
-        let width = self.cell_size.y * self.num_cells.y as f32;
+        let Vec2 {
+            x: width,
+            y: height,
+        } = self.drawn_size();
 
-        if pos.min_element().signum() == -1.0 || pos.x > width || pos.y > height {
+        if pos.min_element().signum() == -1.0 || pos.x >= width || pos.y >= height {
             return None;
         }
-        let y = ((height - pos.y) / 128.).floor() as u16;
-        let x = (pos.x / 128.0).floor() as u16;
-        Some(GridPosition { x, y })
+        let cell = U16Vec2 {
+            x: (pos.x / self.cell_size.x).floor() as u16,
+            y: (pos.y / self.cell_size.y).floor() as u16,
+        };
+        Some(self.orientation.square_at(cell, self.num_cells))
+    }
+    /// Top left corner of the square at `p`. Every square is drawn through
+    /// this, so that the board's orientation applies to all of them.
+    pub fn world_from_grid(&self, p: GridPosition) -> Vec2 {
+        self.orientation.screen_cell(p, self.num_cells).as_vec2() * self.cell_size
+    }
+
+    /// Picks up the piece at `from`, or moves the one already picked up, so
+    /// that it is drawn centered on `world`.
+    pub fn drag(&mut self, from: GridPosition, world: Vec2) {
+        self.dragged = Some((from, world - self.cell_size / 2.0));
+    }
+    /// Lets go of the dragged piece, which slides back into its square unless
+    /// it was moved.
+    pub fn release_drag(&mut self) {
+        if let Some((square, start)) = self.dragged.take() {
+            self.animations.push(MoveAnimation {
+                square,
+                start,
+                progress: 0.0,
+            });
+        }
+    }
+    /// Moves the animations along by `dt` seconds, each one taking `duration`.
+    pub fn advance_animations(&mut self, dt: f32, duration: f32) {
+        let step = if duration > 0.0 { dt / duration } else { 1.0 };
+        for animation in self.animations.iter_mut() {
+            animation.progress += step;
+        }
+        self.animations.retain(|a| a.progress < 1.0);
+    }
+    /// Slides every piece that arrived on a square since `before` from the
+    /// square it left, or from where it was dropped when dragged there.
+    fn animate_arrivals(&mut self, before: &HashMap<GridPosition, Piece>) {
+        let same = |a: &Piece, b: &Piece| a.kind == b.kind && a.color == b.color;
+        let after = self.players.pieces();
+        let mut departed: Vec<&Piece> = before
+            .values()
+            .filter(|p| after.get(&p.position).is_none_or(|a| !same(a, p)))
+            .collect();
+        let dragged = self.dragged.take();
+        self.animations.clear();
+        for arrived in after.values() {
+            if before
+                .get(&arrived.position)
+                .is_some_and(|b| same(b, arrived))
+            {
+                continue;
+            }
+            let Some(idx) = departed.iter().position(|d| same(d, arrived)) else {
+                continue;
+            };
+            let origin = departed.swap_remove(idx).position;
+            let start = match dragged {
+                Some((square, world)) if square == origin => world,
+                _ => self.world_from_grid(origin),
+            };
+            self.animations.push(MoveAnimation {
+                square: arrived.position,
+                start,
+                progress: 0.0,
+            });
+        }
     }
 
     pub fn selected_piece(&self) -> Option<&Piece> {
@@ -168,6 +713,63 @@ impl Board {
     #[inline]
     pub fn select_piece_at(&mut self, position: GridPosition) {
         self.selected_piece_pos = Some(position);
+        self.selected_drop = None;
+    }
+    #[inline]
+    pub fn selected_drop(&self) -> Option<PieceKind> {
+        self.selected_drop
+    }
+    #[inline]
+    pub fn select_drop(&mut self, kind: PieceKind) {
+        self.selected_drop = Some(kind);
+        self.selected_piece_pos = None;
+    }
+
+    /// Distinct pieces in `color`'s pocket with their counts, in display order.
+    fn pocket_slots(&self, color: PieceColor) -> Vec<(PieceKind, usize)> {
+        let pocket = self.players.pocket(color);
+        POCKET_ORDER
+            .iter()
+            .map(|&kind| (kind, pocket.iter().filter(|k| **k == kind).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+    /// Pockets are drawn to the right of the board, Black's along the top edge
+    /// and White's along the bottom one, or the other way around when flipped.
+    fn pocket_slot_rect(&self, color: PieceColor, idx: usize) -> Rect {
+        let height = self.drawn_size().y;
+        let top = if self.orientation.is_flipped() {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        let y = if color == top {
+            0.0
+        } else {
+            height - POCKET_SLOT_SIZE
+        };
+        Rect {
+            x: BOARD_SIZE + POCKET_MARGIN + idx as f32 * POCKET_SLOT_SIZE,
+            y,
+            w: POCKET_SLOT_SIZE,
+            h: POCKET_SLOT_SIZE,
+        }
+    }
+    /// The pocket piece drawn at `pos`, if any.
+    pub fn pocket_slot_at(&self, pos: Vec2) -> Option<(PieceColor, PieceKind)> {
+        if !self.variant.has_drops() {
+            return None;
+        }
+        [PieceColor::Black, PieceColor::White]
+            .into_iter()
+            .flat_map(|color| {
+                self.pocket_slots(color)
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(idx, (kind, _))| (color, idx, kind))
+            })
+            .find(|(color, idx, _)| self.pocket_slot_rect(*color, *idx).contains(pos))
+            .map(|(color, _, kind)| (color, kind))
     }
 
     pub fn try_move_piece(
@@ -178,17 +780,473 @@ impl Board {
         if self.selected_piece_pos.is_some_and(|p| p == from) {
             self.selected_piece_pos.take();
         }
-        self.players.move_piece(self.snapshot(), from, to)
+        let piece = self
+            .players
+            .piece_at(from)
+            .ok_or(piece::MoveError::InvalidOrigin)?
+            .clone();
+        if self.result.is_some() {
+            return Err(piece::MoveError::GameOver);
+        }
+        if piece.color != self.turn || self.duck_pending {
+            return Err(piece::MoveError::WrongTurn);
+        }
+        let to = self.castling_rook_for(&piece, to).unwrap_or(to);
+        let promotion = self.promotion_for(&piece, to);
+        if promotion
+            .as_ref()
+            .is_some_and(|p| p.choices.is_empty() && !p.optional)
+        {
+            return Err(piece::MoveError::NoPromotion);
+        }
+        let san = san::move_san(self, &piece, to);
+        let before = self.players.pieces();
+        let captured = self.players.move_piece(self.snapshot(), from, to)?;
+        self.animate_arrivals(&before);
+        self.last_move = Some((Some(from), to));
+        self.current_move = Some(PlayedMove {
+            color: piece.color,
+            fullmove: self.fullmove,
+            san,
+        });
+        if let Some(captured) = &captured {
+            self.players
+                .add_score(piece.color, rules::piece_points(captured));
+        }
+        if self.variant.has_drops()
+            && let Some(captured) = &captured
+        {
+            let kind = match self.variant.shogi() {
+                Some(shogi) if captured.promoted => shogi.demote(captured.kind),
+                _ if captured.promoted => PieceKind::Pawn,
+                _ => captured.kind,
+            };
+            self.players.add_to_pocket(piece.color, kind);
+        }
+
+        let double_step = from.x.abs_diff(to.x) == 2 || from.y.abs_diff(to.y) == 2;
+        self.en_passant = (piece.kind == PieceKind::Pawn && double_step).then(|| GridPosition {
+            x: (from.x + to.x) / 2,
+            y: (from.y + to.y) / 2,
+        });
+        if piece.kind == PieceKind::King {
+            self.castling.clear(piece.color);
+        }
+        self.castling.clear_square(from, self.num_cells.y - 1);
+        self.castling.clear_square(to, self.num_cells.y - 1);
+
+        if piece.kind == PieceKind::Pawn || captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if let Some(promotion) = promotion.filter(|p| !p.choices.is_empty()) {
+            if promotion.choices.len() == 1 && !promotion.optional {
+                self.players.promote(to, promotion.choices[0]);
+                self.note_promotion(promotion.choices[0]);
+            } else {
+                self.pending_promotion = Some(promotion);
+            }
+        }
+        if self.variant.has_duck() && captured.is_none_or(|c| c.kind != PieceKind::King) {
+            self.duck_pending = true;
+        } else {
+            self.end_turn();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn duck_pending(&self) -> bool {
+        self.duck_pending
+    }
+    /// Moves the duck to `to`, completing the turn of the side to move.
+    pub fn try_place_duck(&mut self, to: GridPosition) -> Result<(), piece::MoveError> {
+        if !self.duck_pending || self.pending_promotion.is_some() {
+            return Err(piece::MoveError::WrongTurn);
+        }
+        if !self.legal_duck_squares().contains(&to) {
+            return Err(piece::MoveError::InvalidTarget);
+        }
+        self.duck = Some(to);
+        self.duck_pending = false;
+        if let Some(current) = &mut self.current_move {
+            current.san.push_str(&format!(",@{}", san::square_name(to)));
+        }
+        self.end_turn();
+        Ok(())
+    }
+    /// Squares the duck may move to, any empty square other than its own.
+    pub fn legal_duck_squares(&self) -> Vec<GridPosition> {
+        let mut res = vec![];
+        for y in 0..self.num_cells.y {
+            for x in 0..self.num_cells.x {
+                let to = GridPosition { x, y };
+                if self.shape.is_playable(to)
+                    && self.duck != Some(to)
+                    && self.piece_at(to).is_none()
+                {
+                    res.push(to);
+                }
+            }
+        }
+        res
+    }
+
+    /// Drops a `kind` from the pocket of the side to move onto `to`.
+    pub fn try_drop(&mut self, kind: PieceKind, to: GridPosition) -> Result<(), piece::MoveError> {
+        self.selected_drop = None;
+        if self.result.is_some() {
+            return Err(piece::MoveError::GameOver);
+        }
+        if !self.players.pocket(self.turn).contains(&kind) {
+            return Err(piece::MoveError::InvalidOrigin);
+        }
+        if !self.legal_drops(kind).contains(&to) {
+            return Err(piece::MoveError::InvalidTarget);
+        }
+        self.current_move = Some(PlayedMove {
+            color: self.turn,
+            fullmove: self.fullmove,
+            san: san::drop_san(kind, to),
+        });
+        self.last_move = Some((None, to));
+        self.players.take_from_pocket(self.turn, kind);
+        self.players.place(Piece {
+            kind,
+            color: self.turn,
+            position: to,
+            promoted: false,
+        });
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        self.end_turn();
+        Ok(())
+    }
+    /// Squares a `kind` from the pocket of the side to move may be dropped on.
+    /// Any empty square is allowed, except for pawns on the first and last
+    /// ranks and drops that would leave the own king in check. Variants with
+    /// [`shogi::ShogiRules`] restrict drops with those instead of the ranks.
+    pub fn legal_drops(&self, kind: PieceKind) -> Vec<GridPosition> {
+        let color = self.turn;
+        let shogi = self.variant.shogi();
+        let state = self.snapshot();
+        let mut res = vec![];
+        for y in 0..self.num_cells.y {
+            if shogi.is_none() && kind == PieceKind::Pawn && (y == 0 || y == self.num_cells.y - 1) {
+                continue;
+            }
+            for x in 0..self.num_cells.x {
+                let to = GridPosition { x, y };
+                if !self.shape.is_playable(to)
+                    || self.duck == Some(to)
+                    || self.piece_at(to).is_some()
+                {
+                    continue;
+                }
+                if shogi.is_some_and(|shogi| {
+                    let enemy_pocket = self.players.pocket(color.opposite());
+                    !shogi.allows_drop(&state, kind, color, to, enemy_pocket)
+                }) {
+                    continue;
+                }
+                let mut snapshot = state.clone();
+                snapshot.place(Piece {
+                    kind,
+                    color,
+                    position: to,
+                    promoted: false,
+                });
+                if self.variant.rules().is_legal(&mut snapshot, color) {
+                    res.push(to);
+                }
+            }
+        }
+        res
+    }
+    fn end_turn(&mut self) {
+        let mover = self.turn;
+        self.pass_turn();
+        self.finish_move(mover);
+    }
+    /// Hands the turn to the next side still in the game.
+    fn pass_turn(&mut self) {
+        let (next, wrapped) = self.players.next_after(self.turn);
+        if wrapped {
+            self.fullmove += 1;
+        }
+        self.turn = next;
+    }
+    /// Counts checks, eliminates sides left without moves when more than two
+    /// play, and reevaluates the outcome. This is deferred while a promotion
+    /// is pending as the chosen piece may change all of them.
+    fn finish_move(&mut self, mover: PieceColor) {
+        if self.pending_promotion.is_some() {
+            return;
+        }
+        let rules = self.variant.rules();
+        let check = rules.in_check(&mut self.snapshot(), self.turn);
+        if check {
+            self.players.add_check(mover);
+        }
+        if self.variant.colors().len() > 2 {
+            while self.players.active().count() > 1 && !self.has_legal_moves() {
+                let eliminated = self.turn;
+                if rules.in_check(&mut self.snapshot(), eliminated) {
+                    self.players.add_score(mover, rules::ELIMINATION_POINTS);
+                } else {
+                    self.players
+                        .add_score(eliminated, rules::ELIMINATION_POINTS);
+                }
+                self.pass_turn();
+                self.players.eliminate(eliminated);
+            }
+        }
+        self.result = rules.result(self);
+        if let Some(mut played) = self.current_move.take() {
+            if let Some(GameResult::Win {
+                reason: WinReason::Checkmate,
+                ..
+            }) = self.result
+            {
+                played.san.push('#');
+            } else if check {
+                played.san.push('+');
+            }
+            self.moves.push(played);
+        }
+    }
+    /// Called when `color` runs out of time. It loses, unless its opponent
+    /// couldn't checkmate, which draws. With more than two sides it is
+    /// eliminated instead.
+    pub fn flag_fall(&mut self, color: PieceColor) {
+        if self.result.is_some() {
+            return;
+        }
+        if self.variant.colors().len() > 2 {
+            // A move left waiting for its promotion is dropped.
+            if self.viewer() == color {
+                self.current_move = None;
+            }
+            self.pending_promotion.take_if(|p| p.color == color);
+            if self.turn == color {
+                self.pass_turn();
+            }
+            self.players.eliminate(color);
+            self.result = self.variant.rules().result(self);
+            return;
+        }
+        let opponent = color.opposite();
+        let pieces = self.players.pieces();
+        let can_mate = rules::has_mating_material(
+            pieces.values().filter(|p| p.color == opponent),
+            self.players.pocket(opponent),
+        );
+        self.result = Some(if can_mate {
+            GameResult::Win {
+                winner: opponent,
+                reason: WinReason::Timeout,
+            }
+        } else {
+            GameResult::Draw(rules::DrawReason::TimeoutVsInsufficientMaterial)
+        });
+    }
+    fn note_promotion(&mut self, kind: PieceKind) {
+        if let Some(current) = &mut self.current_move {
+            current.san.push('=');
+            current.san.push(san::piece_letter(kind));
+        }
+    }
+    /// Whether the side to move has any legal move or drop.
+    pub fn has_legal_moves(&self) -> bool {
+        self.players
+            .pieces()
+            .values()
+            .filter(|p| p.color == self.turn)
+            .any(|p| !self.legal_moves(p.position).is_empty())
+            || self
+                .players
+                .pocket(self.turn)
+                .iter()
+                .any(|k| !self.legal_drops(*k).is_empty())
+    }
+
+    /// Moves the piece at `from` can legally make.
+    /// Castling shows up both as the king moving onto its rook and, when the
+    /// king travels more than one square, as the king's destination.
+    pub fn legal_moves(&self, from: GridPosition) -> Vec<GridPosition> {
+        let Some(piece) = self.players.piece_at(from) else {
+            return vec![];
+        };
+        let mut res = piece.pseudo_moveset(&self.snapshot());
+        res.retain(|&mov| {
+            if self
+                .promotion_for(piece, mov)
+                .is_some_and(|p| p.choices.is_empty() && !p.optional)
+            {
+                return false;
+            }
+            piece.clone().move_to(&mut self.snapshot(), mov).is_ok()
+        });
+        if piece.kind == PieceKind::King {
+            let destinations: Vec<GridPosition> = res
+                .iter()
+                .filter(|m| self.piece_at(**m).is_some_and(|p| p.color == piece.color))
+                .map(|rook| GridPosition {
+                    x: CastlingSide::of(from.x, rook.x)
+                        .destinations(self.num_cells.x)
+                        .0,
+                    y: from.y,
+                })
+                .filter(|dest| dest.x.abs_diff(from.x) > 1)
+                .collect();
+            res.extend(destinations);
+        }
+        res
+    }
+
+    /// Square of the rook to castle with when `piece` is a king moving two or
+    /// more squares onto a castling destination.
+    fn castling_rook_for(&self, piece: &Piece, to: GridPosition) -> Option<GridPosition> {
+        if piece.kind != PieceKind::King
+            || to.y != piece.position.y
+            || to.x.abs_diff(piece.position.x) < 2
+        {
+            return None;
+        }
+        self.castling
+            .rook_files(piece.color)
+            .map(|x| GridPosition {
+                x,
+                y: piece.position.y,
+            })
+            .find(|rook| {
+                CastlingSide::of(piece.position.x, rook.x)
+                    .destinations(self.num_cells.x)
+                    .0
+                    == to.x
+            })
+    }
+
+    /// Promotion a pawn moving to `to` would be offered, `None` if `piece`
+    /// isn't a pawn or `to` is outside the promotion zone.
+    fn promotion_for(&self, piece: &Piece, to: GridPosition) -> Option<PendingPromotion> {
+        if let Some(shogi) = self.variant.shogi() {
+            let (kind, optional) = shogi.promotion(&self.snapshot(), piece, to)?;
+            return Some(PendingPromotion {
+                position: to,
+                color: piece.color,
+                choices: vec![kind],
+                optional,
+            });
+        }
+        if piece.kind != PieceKind::Pawn {
+            return None;
+        }
+        let rule = self.variant.promotion();
+        let rank = piece.color.relative_rank(to, self.num_cells);
+        let last = piece.color.depth(self.num_cells) - 1;
+        if rank + rule.zone <= last {
+            return None;
+        }
+        Some(PendingPromotion {
+            position: to,
+            color: piece.color,
+            choices: rule.available(self.players.lost(piece.color)),
+            optional: !rule.mandatory && rank != last,
+        })
+    }
+
+    #[inline]
+    pub fn pending_promotion(&self) -> Option<&PendingPromotion> {
+        self.pending_promotion.as_ref()
+    }
+    /// Resolves the pending promotion with the choice drawn at `clicked`.
+    /// Clicking anywhere else declines the promotion when it is optional.
+    pub fn resolve_promotion(&mut self, clicked: GridPosition) {
+        let Some(pending) = &self.pending_promotion else {
+            return;
+        };
+        self.choose_promotion(pending.choice_at(clicked));
+    }
+    /// Resolves the pending promotion with `choice`, when it is one of the
+    /// choices offered. Anything else declines the promotion when it is
+    /// optional.
+    pub fn choose_promotion(&mut self, choice: Option<PieceKind>) {
+        let Some(pending) = &self.pending_promotion else {
+            return;
+        };
+        let mover = pending.color;
+        match choice.filter(|kind| pending.choices.contains(kind)) {
+            Some(kind) => {
+                self.players.promote(pending.position, kind);
+                self.pending_promotion = None;
+                self.note_promotion(kind);
+            }
+            None if pending.optional => self.pending_promotion = None,
+            None => return,
+        }
+        // With a duck to move the turn isn't over yet.
+        if !self.duck_pending {
+            self.finish_move(mover);
+        }
+    }
+    /// Resolves the pending promotion like [`Board::choose_promotion`], but
+    /// takes a queen, or the first piece offered without one, when `choice`
+    /// isn't offered and the promotion can't be declined.
+    pub fn choose_promotion_or_default(&mut self, choice: Option<PieceKind>) {
+        self.choose_promotion(choice);
+        let default = self.pending_promotion.as_ref().and_then(|pending| {
+            let choices = &pending.choices;
+            choices
+                .iter()
+                .find(|&&kind| kind == PieceKind::Queen)
+                .or(choices.first())
+                .copied()
+        });
+        if default.is_some() {
+            self.choose_promotion(default);
+        }
     }
 
     fn snapshot(&self) -> BoardState {
         BoardState::new(self)
     }
+    pub fn in_check(&self, color: PieceColor) -> bool {
+        self.variant.rules().in_check(&mut self.snapshot(), color)
+    }
 
     pub fn piece_at(&self, p: GridPosition) -> Option<&Piece> {
         self.players.piece_at(p)
     }
 }
+#[cfg(test)]
+impl Board {
+    /// A board for `position` whose sprites are never drawn, so that tests
+    /// need no window.
+    pub fn headless(variant: Variant, position: Position) -> Self {
+        let texture = || {
+            Texture2D::from_miniquad_texture(macroquad::miniquad::TextureId::from_raw_id(
+                macroquad::miniquad::RawId::OpenGl(0),
+            ))
+        };
+        let mappings = serde_json::from_str(
+            &std::fs::read_to_string("assets/themes/pieces/classic/pieces.json").unwrap(),
+        )
+        .unwrap();
+        Self::from_position(
+            variant,
+            position,
+            SpritesMap::new(texture(), mappings),
+            SpritesMap::new(texture(), mappings),
+            texture(),
+        )
+    }
+    /// A headless board for `fen`.
+    pub fn from_fen(variant: Variant, fen: &str) -> Self {
+        Self::headless(variant, fen.parse().unwrap())
+    }
+}
 // TODO: Probably the 'simplest' way to implement an ahead of turn check
 // is via something along the lines of a CheckValidator that would hold an
 // (immutable) reference to the board + a simulated movement. It would then
@@ -196,17 +1254,28 @@ impl Board {
 // This would allow to keep the drawing immutable and would very possibly greatly
 // simplify the check simulation by being a proper simulation.
 
+#[derive(Clone)]
 pub struct BoardState {
     state: HashMap<GridPosition, Piece>,
+    variant: Variant,
     num_cells: U16Vec2,
-    attack_map: Option<Vec<GridPosition>>,
+    shape: Rc<BoardShape>,
+    duck: Option<GridPosition>,
+    castling: CastlingRights,
+    en_passant: Option<GridPosition>,
+    attack_map: Option<(PieceColor, Vec<GridPosition>)>,
 }
 impl BoardState {
     pub fn new(board: &Board) -> Self {
         let state = board.players.pieces();
         Self {
             state,
+            variant: board.variant,
             num_cells: board.num_cells,
+            shape: board.shape.clone(),
+            duck: board.duck,
+            castling: board.castling,
+            en_passant: board.en_passant,
             attack_map: None,
         }
     }
@@ -218,28 +1287,94 @@ impl BoardState {
         if flags.contains(SquareQueryFlags::IN_BOUNDS) {
             res &= (0..self.num_cells.x).contains(&x) && (0..self.num_cells.y).contains(&y);
         }
+        if flags.contains(SquareQueryFlags::PLAYABLE) {
+            res &= self.shape.is_playable(pos);
+        }
+        if flags.contains(SquareQueryFlags::UNBLOCKED) {
+            res &= self.duck != Some(pos);
+        }
         res
     }
+    /// Square reached by moving `delta` from `from`, see [`BoardShape::step`].
+    #[inline]
+    pub fn step<T: Into<i32>>(&self, from: GridPosition, delta: (T, T)) -> Option<GridPosition> {
+        self.shape.step(from, delta)
+    }
+    /// Every side of the variant other than `color`.
+    pub fn enemies(&self, color: PieceColor) -> impl Iterator<Item = PieceColor> + use<> {
+        self.variant
+            .colors()
+            .iter()
+            .copied()
+            .filter(move |c| *c != color)
+    }
     pub fn attack_map(&mut self, color: PieceColor) -> &[GridPosition] {
-        if self.attack_map.is_none() {
-            let res = self
-                .state
-                .iter()
-                .filter(|(_, v)| v.color == color)
-                .flat_map(|(_, v)| v.pseudo_moveset(self))
-                .collect();
-            self.attack_map = Some(res);
+        if self.attack_map.as_ref().is_none_or(|(c, _)| *c != color) {
+            self.attack_map = Some((color, self.attacked_by(color)));
         }
-        self.attack_map.as_ref().unwrap()
+        &self.attack_map.as_ref().unwrap().1
+    }
+    /// Squares `color` occupies or could move to, which is what it sees in
+    /// fog of war.
+    pub fn visible_to(&self, color: PieceColor) -> HashSet<GridPosition> {
+        self.state
+            .values()
+            .filter(|p| p.color == color)
+            .flat_map(|p| {
+                let mut squares = p.pseudo_moveset(self);
+                squares.push(p.position);
+                squares
+            })
+            .collect()
+    }
+    /// Uncached version of [`BoardState::attack_map`].
+    pub fn attacked_by(&self, color: PieceColor) -> Vec<GridPosition> {
+        self.state
+            .values()
+            .filter(|v| v.color == color)
+            .flat_map(|v| v.attacks(self))
+            .collect()
+    }
+    pub fn king_position(&self, color: PieceColor) -> Option<GridPosition> {
+        self.state
+            .values()
+            .find(|p| p.kind == PieceKind::King && p.color == color)
+            .map(|p| p.position)
     }
     /// Moves piece at `from` to `to`.
     /// Will assume any movement is valid and won't check if it would be a
     /// valid move. If a piece exists at `to` it gets "captured" and is returned.
     /// This only `from` contains a piece.
+    /// A pawn moving diagonally to an empty square captures en passant, and a
+    /// king moving onto its own rook castles with it.
     pub fn move_piece(&mut self, from: GridPosition, to: GridPosition) -> Option<Piece> {
         let moved = self.state.remove(&from);
         if let Some(mut p) = moved {
-            let taken = self.state.remove(&to);
+            self.attack_map = None;
+            let mut taken = self.state.remove(&to);
+            if let Some(mut rook) =
+                taken.take_if(|t| p.kind == PieceKind::King && t.color == p.color)
+            {
+                let (king_to, rook_to) =
+                    CastlingSide::of(from.x, to.x).destinations(self.num_cells.x);
+                p.position = GridPosition {
+                    x: king_to,
+                    y: to.y,
+                };
+                rook.position = GridPosition {
+                    x: rook_to,
+                    y: to.y,
+                };
+                self.state.insert(p.position, p);
+                self.state.insert(rook.position, rook);
+                return None;
+            }
+            if taken.is_none() && p.kind == PieceKind::Pawn && from.x != to.x && from.y != to.y {
+                let (fx, fy) = p.color.front();
+                taken = self
+                    .step(to, (-fx, -fy))
+                    .and_then(|passed| self.state.remove(&passed));
+            }
             p.position = to;
             self.state.insert(to, p);
             taken
@@ -247,7 +1382,71 @@ impl BoardState {
             None
         }
     }
+    pub fn pieces(&self) -> impl Iterator<Item = &Piece> {
+        self.state.values()
+    }
+    pub fn piece_at(&self, position: GridPosition) -> Option<&Piece> {
+        self.state.get(&position)
+    }
+    /// Whether `piece` moving to `to` takes an enemy piece, en passant included.
+    pub fn is_capture(&self, piece: &Piece, to: GridPosition) -> bool {
+        match self.state.get(&to) {
+            Some(p) => p.color != piece.color,
+            None => {
+                piece.kind == PieceKind::Pawn
+                    && to.x != piece.position.x
+                    && to.y != piece.position.y
+            }
+        }
+    }
+    pub fn remove(&mut self, position: GridPosition) -> Option<Piece> {
+        self.attack_map = None;
+        self.state.remove(&position)
+    }
+    /// Puts `piece` on its square, replacing any occupant.
+    pub fn place(&mut self, piece: Piece) {
+        self.attack_map = None;
+        self.state.insert(piece.position, piece);
+    }
+}
+/// A piece sliding into `square`, drawn in place of the piece standing there.
+#[derive(Clone)]
+struct MoveAnimation {
+    square: GridPosition,
+    /// Top left corner the piece starts from.
+    start: Vec2,
+    /// From 0 to 1.
+    progress: f32,
+}
+impl MoveAnimation {
+    /// Progress with an ease-out cubic curve applied.
+    fn eased(&self) -> f32 {
+        1.0 - (1.0 - self.progress).powi(3)
+    }
+}
+
+/// A promotion waiting for the player to pick a piece.
+/// The choices are laid out from the promotion square towards the owner's side.
+#[derive(Clone)]
+pub struct PendingPromotion {
+    position: GridPosition,
+    color: PieceColor,
+    choices: Vec<PieceKind>,
+    optional: bool,
 }
+impl PendingPromotion {
+    fn square_of(&self, idx: usize) -> Option<GridPosition> {
+        let (fx, fy) = self.color.front();
+        let idx = idx as i32;
+        self.position.try_add((-fx as i32 * idx, -fy as i32 * idx))
+    }
+    fn choice_at(&self, position: GridPosition) -> Option<PieceKind> {
+        (0..self.choices.len())
+            .find(|&idx| self.square_of(idx) == Some(position))
+            .map(|idx| self.choices[idx])
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize)]
 pub struct GridPosition {
     x: u16,
@@ -259,7 +1458,7 @@ impl GridPosition {
         let (dx, dy) = (dx.into(), dy.into());
         let x = (self.x as i32).saturating_add(dx);
         let y = (self.y as i32).saturating_add(dy);
-        if (0..u16::MAX as i32).contains(&x) && (0..u16::MAX as i32).contains(&x) {
+        if (0..u16::MAX as i32).contains(&x) && (0..u16::MAX as i32).contains(&y) {
             Some(Self {
                 x: x as u16,
                 y: y as u16,
@@ -281,6 +1480,30 @@ where
         }
     }
 }
+impl FromStr for GridPosition {
+    type Err = ();
+
+    /// Inverse of [`GridPosition`]'s `Display`, case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| c.is_ascii_digit()).ok_or(())?;
+        let (file, rank) = s.split_at(split);
+        if file.is_empty() || !file.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(());
+        }
+        let mut x: u32 = 0;
+        for c in file.chars() {
+            x = x * 26 + (c.to_ascii_uppercase() as u8 - b'A') as u32 + 1;
+        }
+        let y: u16 = rank.parse().map_err(|_| ())?;
+        if y == 0 || x > u16::MAX as u32 {
+            return Err(());
+        }
+        Ok(Self {
+            x: (x - 1) as u16,
+            y: y - 1,
+        })
+    }
+}
 impl Display for GridPosition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut s = String::new();
@@ -303,5 +1526,154 @@ bitflags::bitflags! {
     #[derive(Debug, Clone, Copy)]
     pub struct SquareQueryFlags: u8 {
         const IN_BOUNDS         = 1 << 0;
+        /// Neither a hole nor a wall, see [`SquareKind`].
+        const PLAYABLE          = 1 << 1;
+        /// Not taken by a neutral blocker such as the duck.
+        const UNBLOCKED         = 1 << 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(name: &str) -> GridPosition {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn promotion_defaults_to_a_queen() {
+        let mut board = Board::from_fen(Variant::Standard, "k7/4P3/8/8/8/8/8/4K3 w - - 0 1");
+        board.try_move_piece(square("e7"), square("e8")).unwrap();
+        assert!(board.pending_promotion().is_some());
+        board.choose_promotion_or_default(None);
+        assert!(board.pending_promotion().is_none());
+        assert_eq!(board.piece_at(square("e8")).unwrap().kind, PieceKind::Queen);
+        assert_eq!(board.turn(), PieceColor::Black);
+
+        let mut board = Board::from_fen(Variant::Standard, "k7/4P3/8/8/8/8/8/4K3 w - - 0 1");
+        board.try_move_piece(square("e7"), square("e8")).unwrap();
+        board.choose_promotion_or_default(Some(PieceKind::Knight));
+        assert_eq!(
+            board.piece_at(square("e8")).unwrap().kind,
+            PieceKind::Knight
+        );
+    }
+
+    fn squares(names: &[&str]) -> HashSet<GridPosition> {
+        names.iter().map(|name| square(name)).collect()
+    }
+
+    #[test]
+    fn captures_go_to_the_pocket() {
+        let mut board = Board::from_fen(Variant::Crazyhouse, "4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1");
+        board.try_move_piece(square("e4"), square("d5")).unwrap();
+        assert_eq!(board.players.pocket(PieceColor::White), [PieceKind::Knight]);
+        assert!(board.players.pocket(PieceColor::Black).is_empty());
+    }
+
+    #[test]
+    fn promoted_pieces_go_back_to_the_pocket_as_pawns() {
+        let mut board = Board::from_fen(Variant::Crazyhouse, "4k3/8/8/3q~4/4P3/8/8/4K3 w - - 0 1");
+        board.try_move_piece(square("e4"), square("d5")).unwrap();
+        assert_eq!(board.players.pocket(PieceColor::White), [PieceKind::Pawn]);
+    }
+
+    #[test]
+    fn drops() {
+        let mut board = Board::from_fen(Variant::Crazyhouse, "4k3/8/8/8/8/8/8/4K3[N] w - - 0 1");
+        assert!(matches!(
+            board.try_drop(PieceKind::Queen, square("e4")),
+            Err(piece::MoveError::InvalidOrigin)
+        ));
+        assert!(matches!(
+            board.try_drop(PieceKind::Knight, square("e1")),
+            Err(piece::MoveError::InvalidTarget)
+        ));
+        board.try_drop(PieceKind::Knight, square("e4")).unwrap();
+        let dropped = board.piece_at(square("e4")).unwrap();
+        assert_eq!(
+            (dropped.kind, dropped.color),
+            (PieceKind::Knight, PieceColor::White)
+        );
+        assert!(board.players.pocket(PieceColor::White).is_empty());
+        assert_eq!(board.turn(), PieceColor::Black);
+        assert_eq!(board.moves()[0].san, "N@e4");
+    }
+
+    #[test]
+    fn pawns_are_not_dropped_on_the_first_or_last_rank() {
+        let board = Board::from_fen(Variant::Crazyhouse, "4k3/8/8/8/8/8/8/4K3[P] w - - 0 1");
+        let drops = board.legal_drops(PieceKind::Pawn);
+        assert_eq!(drops.len(), 6 * 8);
+        assert!(drops.iter().all(|p| p.y != 0 && p.y != 7));
+    }
+
+    #[test]
+    fn drops_have_to_answer_check() {
+        let board = Board::from_fen(Variant::Crazyhouse, "4r2k/8/8/8/8/8/8/4K3[N] w - - 0 1");
+        let drops: HashSet<_> = board.legal_drops(PieceKind::Knight).into_iter().collect();
+        assert_eq!(drops, squares(&["e2", "e3", "e4", "e5", "e6", "e7"]));
+    }
+
+    #[test]
+    fn try_add_stays_in_bounds() {
+        let origin = GridPosition { x: 0, y: 0 };
+        assert_eq!(origin.try_add((0, -1)), None);
+        assert_eq!(origin.try_add((-1, 0)), None);
+        assert_eq!(origin.try_add((2, 3)), Some(GridPosition { x: 2, y: 3 }));
+        let top = GridPosition {
+            x: 0,
+            y: u16::MAX - 1,
+        };
+        assert_eq!(top.try_add((0, 1)), None);
+        assert_eq!(
+            top.try_add((1, -1)),
+            Some(GridPosition {
+                x: 1,
+                y: u16::MAX - 2
+            })
+        );
+    }
+
+    #[test]
+    fn the_duck_moves_to_a_different_empty_square() {
+        let start = Variant::Duck.starting_position();
+        let mut board = Board::headless(Variant::Duck, start);
+        board.try_move_piece(square("e2"), square("e4")).unwrap();
+        assert!(board.duck_pending());
+        assert_eq!(board.turn(), PieceColor::White);
+        assert!(matches!(
+            board.try_move_piece(square("d2"), square("d4")),
+            Err(piece::MoveError::WrongTurn)
+        ));
+        assert!(matches!(
+            board.try_place_duck(square("e4")),
+            Err(piece::MoveError::InvalidTarget)
+        ));
+        board.try_place_duck(square("e5")).unwrap();
+        assert!(!board.duck_pending());
+        assert_eq!(board.turn(), PieceColor::Black);
+        assert_eq!(board.moves()[0].san, "e4,@e5");
+
+        board.try_move_piece(square("d7"), square("d6")).unwrap();
+        assert!(matches!(
+            board.try_place_duck(square("e5")),
+            Err(piece::MoveError::InvalidTarget)
+        ));
+        assert!(!board.legal_duck_squares().contains(&square("e5")));
+        board.try_place_duck(square("d5")).unwrap();
+        assert_eq!(board.turn(), PieceColor::White);
+    }
+
+    #[test]
+    fn the_duck_blocks_sliders() {
+        let mut board = Board::from_fen(Variant::Duck, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        board.try_move_piece(square("e1"), square("f1")).unwrap();
+        board.try_place_duck(square("a5")).unwrap();
+        board.try_move_piece(square("e8"), square("e7")).unwrap();
+        board.try_place_duck(square("a4")).unwrap();
+        let rook: HashSet<_> = board.legal_moves(square("a1")).into_iter().collect();
+        assert_eq!(rook, squares(&["a2", "a3", "b1", "c1", "d1", "e1"]));
     }
 }
@@ -0,0 +1,130 @@
+use macroquad::color::Color;
+
+use super::{GridPosition, san::square_name};
+
+/// Colors of the marks drawn while analysing, as lichess names them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+impl MarkColor {
+    /// Green by default, red with shift, blue with alt and yellow with both
+    /// or with ctrl.
+    pub fn from_modifiers(shift: bool, alt: bool, ctrl: bool) -> Self {
+        match (shift, alt, ctrl) {
+            (true, true, _) | (_, _, true) => MarkColor::Yellow,
+            (true, false, _) => MarkColor::Red,
+            (false, true, _) => MarkColor::Blue,
+            (false, false, _) => MarkColor::Green,
+        }
+    }
+    fn letter(self) -> char {
+        match self {
+            MarkColor::Green => 'G',
+            MarkColor::Red => 'R',
+            MarkColor::Yellow => 'Y',
+            MarkColor::Blue => 'B',
+        }
+    }
+    pub fn color(self) -> Color {
+        match self {
+            MarkColor::Green => Color {
+                r: 0.08,
+                g: 0.47,
+                b: 0.11,
+                a: 0.8,
+            },
+            MarkColor::Red => Color {
+                r: 0.53,
+                g: 0.13,
+                b: 0.13,
+                a: 0.8,
+            },
+            MarkColor::Yellow => Color {
+                r: 0.9,
+                g: 0.68,
+                b: 0.0,
+                a: 0.8,
+            },
+            MarkColor::Blue => Color {
+                r: 0.0,
+                g: 0.19,
+                b: 0.53,
+                a: 0.8,
+            },
+        }
+    }
+}
+
+/// Arrows and circled squares drawn on a position.
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    squares: Vec<(GridPosition, MarkColor)>,
+    arrows: Vec<(GridPosition, GridPosition, MarkColor)>,
+}
+impl Annotations {
+    #[inline]
+    pub fn squares(&self) -> &[(GridPosition, MarkColor)] {
+        &self.squares
+    }
+    #[inline]
+    pub fn arrows(&self) -> &[(GridPosition, GridPosition, MarkColor)] {
+        &self.arrows
+    }
+    pub fn is_empty(&self) -> bool {
+        self.squares.is_empty() && self.arrows.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.squares.clear();
+        self.arrows.clear();
+    }
+    /// Circles `square`, or removes its circle when already drawn in `color`.
+    pub fn toggle_square(&mut self, square: GridPosition, color: MarkColor) {
+        let existing = self.squares.iter().position(|(s, _)| *s == square);
+        match existing {
+            Some(idx) if self.squares[idx].1 == color => {
+                self.squares.remove(idx);
+            }
+            Some(idx) => self.squares[idx].1 = color,
+            None => self.squares.push((square, color)),
+        }
+    }
+    /// Draws an arrow, or removes it when already drawn in `color`.
+    pub fn toggle_arrow(&mut self, from: GridPosition, to: GridPosition, color: MarkColor) {
+        let existing = self
+            .arrows
+            .iter()
+            .position(|(f, t, _)| *f == from && *t == to);
+        match existing {
+            Some(idx) if self.arrows[idx].2 == color => {
+                self.arrows.remove(idx);
+            }
+            Some(idx) => self.arrows[idx].2 = color,
+            None => self.arrows.push((from, to, color)),
+        }
+    }
+    /// The `[%csl]` and `[%cal]` commands of a PGN comment.
+    pub fn to_pgn_commands(&self) -> String {
+        let mut res = String::new();
+        if !self.squares.is_empty() {
+            let squares: Vec<String> = self
+                .squares
+                .iter()
+                .map(|(s, c)| format!("{}{}", c.letter(), square_name(*s)))
+                .collect();
+            res.push_str(&format!("[%csl {}]", squares.join(",")));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self
+                .arrows
+                .iter()
+                .map(|(f, t, c)| format!("{}{}{}", c.letter(), square_name(*f), square_name(*t)))
+                .collect();
+            res.push_str(&format!("[%cal {}]", arrows.join(",")));
+        }
+        res
+    }
+}
@@ -0,0 +1,76 @@
+use super::{GridPosition, piece::PieceColor};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CastlingSide {
+    /// Towards the last file.
+    King,
+    /// Towards the first file.
+    Queen,
+}
+impl CastlingSide {
+    /// Side of a rook at `rook_file` relative to a king at `king_file`.
+    pub fn of(king_file: u16, rook_file: u16) -> Self {
+        if rook_file > king_file {
+            CastlingSide::King
+        } else {
+            CastlingSide::Queen
+        }
+    }
+    /// Files the king and the rook land on, as `(king, rook)`.
+    /// These are the standard squares regardless of where both started, which
+    /// makes the same rule work for Chess960 and wider boards.
+    pub fn destinations(self, files: u16) -> (u16, u16) {
+        match self {
+            CastlingSide::King => (files - 2, files - 3),
+            CastlingSide::Queen => (2, 3),
+        }
+    }
+}
+
+/// Files of the rooks each side may still castle with, indexed by color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CastlingRights {
+    sides: [[Option<u16>; 2]; 4],
+}
+impl CastlingRights {
+    fn sides(&self, color: PieceColor) -> &[Option<u16>; 2] {
+        &self.sides[color as usize]
+    }
+    fn sides_mut(&mut self, color: PieceColor) -> &mut [Option<u16>; 2] {
+        &mut self.sides[color as usize]
+    }
+    fn index(side: CastlingSide) -> usize {
+        match side {
+            CastlingSide::King => 0,
+            CastlingSide::Queen => 1,
+        }
+    }
+
+    pub fn get(&self, color: PieceColor, side: CastlingSide) -> Option<u16> {
+        self.sides(color)[Self::index(side)]
+    }
+    pub fn set(&mut self, color: PieceColor, side: CastlingSide, rook_file: Option<u16>) {
+        self.sides_mut(color)[Self::index(side)] = rook_file;
+    }
+    pub fn rook_files(&self, color: PieceColor) -> impl Iterator<Item = u16> + '_ {
+        self.sides(color).iter().flatten().copied()
+    }
+
+    pub fn clear(&mut self, color: PieceColor) {
+        *self.sides_mut(color) = [None; 2];
+    }
+    /// Drops the rights that depend on a rook standing at `position`, used once
+    /// anything moves from or to that square.
+    pub fn clear_square(&mut self, position: GridPosition, last_rank: u16) {
+        for (color, rank) in [(PieceColor::White, 0), (PieceColor::Black, last_rank)] {
+            if position.y != rank {
+                continue;
+            }
+            for file in self.sides_mut(color) {
+                if *file == Some(position.x) {
+                    *file = None;
+                }
+            }
+        }
+    }
+}
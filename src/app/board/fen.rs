@@ -0,0 +1,389 @@
+use std::{fmt::Display, str::FromStr};
+
+use macroquad::math::U16Vec2;
+
+use super::{
+    GridPosition,
+    castling::{CastlingRights, CastlingSide},
+    piece::{Piece, PieceColor, PieceKind},
+    shape::{BoardShape, SquareKind},
+};
+
+/// Everything needed to resume a game, as described by a FEN record.
+/// FEN only tells White and Black apart, the side armies of four-player chess
+/// are written as Black's.
+#[derive(Clone, Debug)]
+pub struct Position {
+    /// Walls are written as `*`, holes aren't part of FEN and are read as
+    /// empty squares.
+    pub shape: BoardShape,
+    pub pieces: Vec<Piece>,
+    /// Pieces in hand for variants with drops, written as `[QNp]` after the
+    /// piece placement.
+    pub pockets: Vec<(PieceKind, PieceColor)>,
+    pub turn: PieceColor,
+    pub castling: CastlingRights,
+    pub en_passant: Option<GridPosition>,
+    /// Moves since the last capture or pawn move.
+    pub halfmove_clock: u32,
+    pub fullmove: u32,
+}
+
+/// How the castling field of a FEN record names the castling rooks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CastlingNotation {
+    /// `KQkq`, falling back to the rook's file when the rook isn't the
+    /// outermost one on its side of the king.
+    #[default]
+    XFen,
+    /// Always the rook's file, such as `HAha`.
+    Shredder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenError {
+    MissingField,
+    InvalidPlacement,
+    InvalidTurn,
+    InvalidCastling,
+    InvalidEnPassant,
+    InvalidCounter,
+}
+
+impl Position {
+    #[inline]
+    pub fn num_cells(&self) -> U16Vec2 {
+        self.shape.num_cells()
+    }
+    fn king_file(&self, color: PieceColor, rank: u16) -> Option<u16> {
+        self.pieces
+            .iter()
+            .find(|p| p.kind == PieceKind::King && p.color == color && p.position.y == rank)
+            .map(|p| p.position.x)
+    }
+    /// File of the outermost rook of `color` on `side` of its king.
+    fn outermost_rook(&self, color: PieceColor, side: CastlingSide) -> Option<u16> {
+        let rank = self.back_rank(color);
+        let king = self.king_file(color, rank)?;
+        let rooks = self
+            .pieces
+            .iter()
+            .filter(|p| p.kind == PieceKind::Rook && p.color == color && p.position.y == rank)
+            .map(|p| p.position.x)
+            .filter(|&x| CastlingSide::of(king, x) == side && x != king);
+        match side {
+            CastlingSide::King => rooks.max(),
+            CastlingSide::Queen => rooks.min(),
+        }
+    }
+    /// Rank the castling pieces of `color` stand on, only White and Black castle.
+    fn back_rank(&self, color: PieceColor) -> u16 {
+        match color {
+            PieceColor::Black => self.num_cells().y - 1,
+            PieceColor::White | PieceColor::Blue | PieceColor::Green => 0,
+        }
+    }
+
+    /// Grants castling with the outermost rook on both sides of each king.
+    pub fn with_default_castling(mut self) -> Self {
+        for color in [PieceColor::White, PieceColor::Black] {
+            for side in [CastlingSide::King, CastlingSide::Queen] {
+                let file = self.outermost_rook(color, side);
+                self.castling.set(color, side, file);
+            }
+        }
+        self
+    }
+    /// Grants castling on `side` with the outermost rook, or takes it back.
+    pub fn toggle_castling(&mut self, color: PieceColor, side: CastlingSide) {
+        let file = match self.castling.get(color, side) {
+            Some(_) => None,
+            None => self.outermost_rook(color, side),
+        };
+        self.castling.set(color, side, file);
+    }
+    /// Drops the castling rights whose king or rook left its back rank.
+    pub fn retain_valid_castling(&mut self) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            let rank = self.back_rank(color);
+            for side in [CastlingSide::King, CastlingSide::Queen] {
+                let Some(file) = self.castling.get(color, side) else {
+                    continue;
+                };
+                let valid = self.king_file(color, rank).is_some_and(|king| {
+                    CastlingSide::of(king, file) == side
+                        && self.pieces.iter().any(|p| {
+                            p.kind == PieceKind::Rook
+                                && p.color == color
+                                && p.position == GridPosition { x: file, y: rank }
+                        })
+                });
+                if !valid {
+                    self.castling.set(color, side, None);
+                }
+            }
+        }
+    }
+
+    pub fn to_fen(&self, notation: CastlingNotation) -> String {
+        let mut res = String::new();
+        let num_cells = self.num_cells();
+        for y in (0..num_cells.y).rev() {
+            let mut empty = 0;
+            for x in 0..num_cells.x {
+                let position = GridPosition { x, y };
+                let piece = self.pieces.iter().find(|p| p.position == position);
+                let wall = self.shape.kind(position) == Some(SquareKind::Wall);
+                if piece.is_none() && !wall {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    res.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                match piece {
+                    Some(p) => {
+                        res.push(p.kind.fen_char(p.color));
+                        if p.promoted {
+                            res.push('~');
+                        }
+                    }
+                    None => res.push('*'),
+                }
+            }
+            if empty > 0 {
+                res.push_str(&empty.to_string());
+            }
+            if y > 0 {
+                res.push('/');
+            }
+        }
+        if !self.pockets.is_empty() {
+            res.push('[');
+            res.extend(
+                self.pockets
+                    .iter()
+                    .map(|(kind, color)| kind.fen_char(*color)),
+            );
+            res.push(']');
+        }
+
+        res.push(' ');
+        res.push(match self.turn {
+            PieceColor::White => 'w',
+            PieceColor::Black | PieceColor::Blue | PieceColor::Green => 'b',
+        });
+
+        res.push(' ');
+        let mut castling = String::new();
+        for color in [PieceColor::White, PieceColor::Black] {
+            for side in [CastlingSide::King, CastlingSide::Queen] {
+                let Some(file) = self.castling.get(color, side) else {
+                    continue;
+                };
+                let c = match notation {
+                    CastlingNotation::XFen if self.outermost_rook(color, side) == Some(file) => {
+                        match side {
+                            CastlingSide::King => 'K',
+                            CastlingSide::Queen => 'Q',
+                        }
+                    }
+                    _ => (b'A' + file as u8) as char,
+                };
+                castling.push(match color {
+                    PieceColor::White => c,
+                    _ => c.to_ascii_lowercase(),
+                });
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        res.push_str(&castling);
+
+        res.push(' ');
+        match self.en_passant {
+            Some(p) => res.push_str(&p.to_string().to_ascii_lowercase()),
+            None => res.push('-'),
+        }
+        res.push_str(&format!(" {} {}", self.halfmove_clock, self.fullmove));
+        res
+    }
+}
+
+impl FromStr for Position {
+    type Err = FenError;
+
+    /// Parses a FEN record. Boards wider or taller than 8 squares are supported
+    /// through multi-digit empty square counts, walls are written as `*`, and
+    /// the castling field may be written in either X-FEN or Shredder-FEN.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+
+        let placement = fields.next().ok_or(FenError::MissingField)?;
+        let (placement, pockets) = match placement.split_once('[') {
+            Some((placement, pockets)) => {
+                let pockets = pockets
+                    .strip_suffix(']')
+                    .ok_or(FenError::InvalidPlacement)?
+                    .chars()
+                    .map(|c| PieceKind::from_fen_char(c).ok_or(FenError::InvalidPlacement))
+                    .collect::<Result<_, _>>()?;
+                (placement, pockets)
+            }
+            None => (placement, vec![]),
+        };
+        let ranks: Vec<&str> = placement.split('/').collect();
+        let height = ranks.len() as u16;
+        let mut width = None;
+        let mut pieces: Vec<Piece> = vec![];
+        let mut walls = vec![];
+        for (idx, rank) in ranks.iter().enumerate() {
+            let y = height - idx as u16 - 1;
+            let mut x = 0;
+            let mut chars = rank.chars().peekable();
+            while let Some(c) = chars.next() {
+                if let Some(d) = c.to_digit(10) {
+                    let mut n = d as u16;
+                    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        n = n * 10 + d as u16;
+                        chars.next();
+                    }
+                    x += n;
+                } else if c == '*' {
+                    walls.push(GridPosition { x, y });
+                    x += 1;
+                } else if c == '~' {
+                    let last = pieces.last_mut().ok_or(FenError::InvalidPlacement)?;
+                    last.promoted = true;
+                } else {
+                    let (kind, color) =
+                        PieceKind::from_fen_char(c).ok_or(FenError::InvalidPlacement)?;
+                    pieces.push(Piece {
+                        kind,
+                        color,
+                        position: GridPosition { x, y },
+                        promoted: false,
+                    });
+                    x += 1;
+                }
+            }
+            if *width.get_or_insert(x) != x {
+                return Err(FenError::InvalidPlacement);
+            }
+        }
+        let mut shape = BoardShape::rectangle(U16Vec2 {
+            x: width.filter(|&w| w > 0).ok_or(FenError::InvalidPlacement)?,
+            y: height,
+        });
+        for wall in walls {
+            shape.set(wall, SquareKind::Wall);
+        }
+
+        let turn = match fields.next() {
+            Some("w") | None => PieceColor::White,
+            Some("b") => PieceColor::Black,
+            Some(_) => return Err(FenError::InvalidTurn),
+        };
+
+        let mut position = Position {
+            shape,
+            pieces,
+            pockets,
+            turn,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove: 1,
+        };
+
+        let castling = fields.next().unwrap_or("-");
+        if castling != "-" {
+            for c in castling.chars() {
+                let color = if c.is_ascii_uppercase() {
+                    PieceColor::White
+                } else {
+                    PieceColor::Black
+                };
+                let rank = position.back_rank(color);
+                let king = position
+                    .king_file(color, rank)
+                    .ok_or(FenError::InvalidCastling)?;
+                let (side, file) = match c.to_ascii_uppercase() {
+                    'K' => (
+                        CastlingSide::King,
+                        position.outermost_rook(color, CastlingSide::King),
+                    ),
+                    'Q' => (
+                        CastlingSide::Queen,
+                        position.outermost_rook(color, CastlingSide::Queen),
+                    ),
+                    f @ 'A'..='Z' => {
+                        let file = (f as u8 - b'A') as u16;
+                        (CastlingSide::of(king, file), Some(file))
+                    }
+                    _ => return Err(FenError::InvalidCastling),
+                };
+                let file = file.ok_or(FenError::InvalidCastling)?;
+                position.castling.set(color, side, Some(file));
+            }
+        }
+
+        position.en_passant = match fields.next() {
+            Some("-") | None => None,
+            Some(s) => Some(s.parse().map_err(|_| FenError::InvalidEnPassant)?),
+        };
+        if let Some(n) = fields.next() {
+            position.halfmove_clock = n.parse().map_err(|_| FenError::InvalidCounter)?;
+        }
+        if let Some(n) = fields.next() {
+            position.fullmove = n.parse().map_err(|_| FenError::InvalidCounter)?;
+        }
+
+        Ok(position)
+    }
+}
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_fen(CastlingNotation::default()))
+    }
+}
+
+impl PieceKind {
+    pub fn fen_char(&self, color: PieceColor) -> char {
+        let c = match self {
+            PieceKind::Pawn => 'p',
+            PieceKind::Rook => 'r',
+            PieceKind::Bishop => 'b',
+            PieceKind::Knight => 'n',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+            PieceKind::Archbishop => 'a',
+            PieceKind::Chancellor => 'c',
+        };
+        match color {
+            PieceColor::White => c.to_ascii_uppercase(),
+            PieceColor::Black | PieceColor::Blue | PieceColor::Green => c,
+        }
+    }
+    pub fn from_fen_char(c: char) -> Option<(PieceKind, PieceColor)> {
+        let kind = match c.to_ascii_lowercase() {
+            'p' => PieceKind::Pawn,
+            'r' => PieceKind::Rook,
+            'b' => PieceKind::Bishop,
+            'n' => PieceKind::Knight,
+            'q' => PieceKind::Queen,
+            'k' => PieceKind::King,
+            'a' => PieceKind::Archbishop,
+            'c' => PieceKind::Chancellor,
+            _ => return None,
+        };
+        let color = if c.is_ascii_uppercase() {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        Some((kind, color))
+    }
+}
@@ -0,0 +1,70 @@
+use macroquad::color::Color;
+
+use super::piece::PieceColor;
+
+/// Translucent layers drawn between the squares and the pieces, each of which
+/// can be switched on and off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HighlightLayers {
+    /// Origin and destination of the last move.
+    pub last_move: bool,
+    /// The king of the side to move, when in check.
+    pub check: bool,
+    /// Pieces attacked by an enemy and guarded by none of their own side.
+    pub hanging: bool,
+    /// Side whose attacked squares are shaded, darker the more pieces attack
+    /// them.
+    pub attacks: Option<PieceColor>,
+}
+impl Default for HighlightLayers {
+    fn default() -> Self {
+        Self {
+            last_move: true,
+            check: true,
+            hanging: false,
+            attacks: None,
+        }
+    }
+}
+impl HighlightLayers {
+    pub const LAST_MOVE: Color = Color {
+        r: 0.8,
+        g: 0.8,
+        b: 0.2,
+        a: 0.45,
+    };
+    pub const CHECK: Color = Color {
+        r: 0.9,
+        g: 0.1,
+        b: 0.1,
+        a: 0.6,
+    };
+    pub const HANGING: Color = Color {
+        r: 1.0,
+        g: 0.3,
+        b: 0.0,
+        a: 0.9,
+    };
+    /// Shade of a square attacked once, stacking with each further attacker.
+    pub const ATTACK: Color = Color {
+        r: 1.0,
+        g: 0.5,
+        b: 0.3,
+        a: 0.2,
+    };
+    /// Most attackers whose shades stack.
+    pub const MAX_ATTACK_SHADES: usize = 3;
+
+    /// Shows the attack map of the next side in `colors`, turning it off after
+    /// the last one.
+    pub fn cycle_attacks(&mut self, colors: &[PieceColor]) {
+        self.attacks = match self.attacks {
+            None => colors.first().copied(),
+            Some(color) => colors
+                .iter()
+                .position(|c| *c == color)
+                .and_then(|idx| colors.get(idx + 1))
+                .copied(),
+        };
+    }
+}
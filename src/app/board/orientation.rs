@@ -0,0 +1,85 @@
+use macroquad::math::U16Vec2;
+
+use super::{GridPosition, piece::PieceColor};
+
+/// How the board is turned on screen, in counterclockwise quarter turns from
+/// White's point of view, where the first rank is at the bottom.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+    quarter_turns: u8,
+}
+impl Orientation {
+    /// Orientation with `color`'s side of the board at the bottom.
+    pub fn of(color: PieceColor) -> Self {
+        let quarter_turns = match color {
+            PieceColor::White => 0,
+            PieceColor::Blue => 1,
+            PieceColor::Black => 2,
+            PieceColor::Green => 3,
+        };
+        Self { quarter_turns }
+    }
+    /// `self` turned further by `other`.
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            quarter_turns: (self.quarter_turns + other.quarter_turns) % 4,
+        }
+    }
+    /// Turned upside down.
+    pub fn flipped(self) -> Self {
+        self.then(Self { quarter_turns: 2 })
+    }
+    /// Turned by 90°, as when passing the board to the next side in
+    /// four-player chess.
+    pub fn rotated(self) -> Self {
+        self.then(Self { quarter_turns: 1 })
+    }
+    /// Whether files run vertically on screen.
+    pub fn is_sideways(self) -> bool {
+        self.quarter_turns % 2 == 1
+    }
+    /// Whether the last rank is drawn at the bottom.
+    pub fn is_flipped(self) -> bool {
+        self.quarter_turns == 2
+    }
+
+    /// Columns and rows of cells the board takes on screen.
+    pub fn screen_cells(self, num_cells: U16Vec2) -> U16Vec2 {
+        if self.is_sideways() {
+            U16Vec2 {
+                x: num_cells.y,
+                y: num_cells.x,
+            }
+        } else {
+            num_cells
+        }
+    }
+    /// Screen cell, counted from the top left corner, `p` is drawn in.
+    pub fn screen_cell(self, p: GridPosition, num_cells: U16Vec2) -> U16Vec2 {
+        let U16Vec2 { x: w, y: h } = num_cells;
+        let GridPosition { x, y } = p;
+        match self.quarter_turns {
+            0 => U16Vec2 { x, y: h - y - 1 },
+            1 => U16Vec2 {
+                x: h - y - 1,
+                y: w - x - 1,
+            },
+            2 => U16Vec2 { x: w - x - 1, y },
+            _ => U16Vec2 { x: y, y: x },
+        }
+    }
+    /// Inverse of [`Orientation::screen_cell`].
+    pub fn square_at(self, cell: U16Vec2, num_cells: U16Vec2) -> GridPosition {
+        let U16Vec2 { x: w, y: h } = num_cells;
+        let U16Vec2 { x, y } = cell;
+        match self.quarter_turns {
+            0 => GridPosition { x, y: h - y - 1 },
+            1 => GridPosition {
+                x: w - y - 1,
+                y: h - x - 1,
+            },
+            2 => GridPosition { x: w - x - 1, y },
+            _ => GridPosition { x: y, y: x },
+        }
+    }
+}
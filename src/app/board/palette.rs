@@ -0,0 +1,45 @@
+use macroquad::{
+    color::{BLACK, Color, DARKBROWN, WHITE},
+    texture::Texture2D,
+};
+
+/// Colors the squares of the board are drawn in.
+#[derive(Clone)]
+pub struct BoardPalette {
+    pub name: String,
+    pub light: Color,
+    pub dark: Color,
+    pub wall: Color,
+    /// Image stretched over the whole board in place of the light and dark
+    /// colors, which are still used for the square labels.
+    pub texture: Option<Texture2D>,
+}
+impl Default for BoardPalette {
+    fn default() -> Self {
+        Self {
+            name: "Contrast".to_string(),
+            light: WHITE,
+            dark: BLACK,
+            wall: DARKBROWN,
+            texture: None,
+        }
+    }
+}
+impl BoardPalette {
+    /// Color of the square at `(x, y)`, the corner ones being dark.
+    pub fn square(&self, x: u16, y: u16) -> Color {
+        if (x + y).is_multiple_of(2) {
+            self.dark
+        } else {
+            self.light
+        }
+    }
+    /// Color of the label of the square at `(x, y)`, readable over it.
+    pub fn label(&self, x: u16, y: u16) -> Color {
+        if (x + y).is_multiple_of(2) {
+            self.light
+        } else {
+            self.dark
+        }
+    }
+}
@@ -1,9 +1,53 @@
-use super::{BoardState, GridPosition, SquareQueryFlags, sprites::PieceMappings};
+use macroquad::math::U16Vec2;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use super::{
+    BoardState, GridPosition, SquareQueryFlags, castling::CastlingSide, sprites::PieceMappings,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PieceColor {
     Black,
     White,
+    /// Four-player chess, starts on the left edge.
+    Blue,
+    /// Four-player chess, starts on the right edge.
+    Green,
+}
+impl PieceColor {
+    /// The side facing this one.
+    pub fn opposite(self) -> Self {
+        match self {
+            PieceColor::Black => PieceColor::White,
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Blue => PieceColor::Green,
+            PieceColor::Green => PieceColor::Blue,
+        }
+    }
+    /// Direction this color's pawns advance in.
+    pub fn front(self) -> (i8, i8) {
+        match self {
+            PieceColor::Black => (0, -1),
+            PieceColor::White => (0, 1),
+            PieceColor::Blue => (1, 0),
+            PieceColor::Green => (-1, 0),
+        }
+    }
+    /// Rank of `position` counted from this color's side of the board, starting at 0.
+    pub fn relative_rank(self, position: GridPosition, num_cells: U16Vec2) -> u16 {
+        match self {
+            PieceColor::Black => num_cells.y - position.y - 1,
+            PieceColor::White => position.y,
+            PieceColor::Blue => position.x,
+            PieceColor::Green => num_cells.x - position.x - 1,
+        }
+    }
+    /// Number of ranks between this color's side and the opposite one.
+    pub fn depth(self, num_cells: U16Vec2) -> u16 {
+        match self {
+            PieceColor::Black | PieceColor::White => num_cells.y,
+            PieceColor::Blue | PieceColor::Green => num_cells.x,
+        }
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PieceKind {
@@ -13,97 +57,151 @@ pub enum PieceKind {
     Knight,
     Queen,
     King,
+    /// Moves as a bishop or a knight.
+    Archbishop,
+    /// Moves as a rook or a knight.
+    Chancellor,
 }
 #[derive(Clone, Debug)]
 pub struct Piece {
     pub kind: PieceKind,
     pub color: PieceColor,
     pub position: GridPosition,
+    /// Whether the piece was a pawn before promoting.
+    pub promoted: bool,
 }
 impl Piece {
     /// Generates pseduolegal moves.
     /// A pseudolegal move is defined as a reachable possible move the piece can take.
     /// The difference with legal moves is that pseudolegal moves dont take into account
     /// some of the game's state and rules such as if the move would lead to a check(mate).
+    /// Castling is encoded as the king moving onto its own rook.
     pub fn pseudo_moveset(&self, board: &BoardState) -> Vec<GridPosition> {
-        let front = match self.color {
-            PieceColor::Black => (0, -1),
-            PieceColor::White => (0, 1),
+        self.moveset(board, false)
+    }
+    /// Squares this piece threatens.
+    /// Unlike [`Piece::pseudo_moveset`] this includes squares guarded by the
+    /// piece's own side and pawn diagonals regardless of their occupant, and
+    /// excludes pawn pushes and castling.
+    pub fn attacks(&self, board: &BoardState) -> Vec<GridPosition> {
+        self.moveset(board, true)
+    }
+    fn moveset(&self, board: &BoardState, attacks: bool) -> Vec<GridPosition> {
+        let front = self.color.front();
+        let targets = if attacks {
+            InclusionPolicy::all()
+        } else {
+            InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT
         };
         let mut res = vec![];
         let mut helper = MoveConstructor {
             start: self.position,
             piece_color: self.color,
             front,
-            square_flags: SquareQueryFlags::IN_BOUNDS,
+            square_flags: SquareQueryFlags::IN_BOUNDS
+                | SquareQueryFlags::PLAYABLE
+                | SquareQueryFlags::UNBLOCKED,
             board,
             result: &mut res,
         };
         match self.kind {
+            PieceKind::Pawn if attacks => {
+                for diagonal in pawn_diagonals(front) {
+                    helper.front = diagonal;
+                    helper.build_straight_line(Some(1), targets);
+                }
+            }
             PieceKind::Pawn => {
-                helper.build_straight_line(Some(1), InclusionPolicy::EMPTY);
-                helper.front.0 = 1;
-                helper.build_straight_line(Some(1), InclusionPolicy::DIFFERENT);
-                helper.front.0 = -1;
-                helper.build_straight_line(Some(1), InclusionPolicy::DIFFERENT);
+                let rank = self.color.relative_rank(self.position, board.num_cells);
+                let steps = if board.variant.double_step_ranks().contains(&rank) {
+                    2
+                } else {
+                    1
+                };
+                helper.build_straight_line(Some(steps), InclusionPolicy::EMPTY);
+                for diagonal in pawn_diagonals(front) {
+                    helper.front = diagonal;
+                    helper.build_straight_line(Some(1), InclusionPolicy::DIFFERENT);
+                    helper.build_en_passant();
+                }
             }
             PieceKind::Rook => {
-                helper.build_cross(None, InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
+                helper.build_cross(None, targets);
             }
             PieceKind::Bishop => {
-                helper.build_diag_cross(None, InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
+                helper.build_diag_cross(None, targets);
             }
             PieceKind::Knight => {
-                helper.build_square_corners((2, 1));
-                helper.build_square_corners((1, 2));
+                helper.build_square_corners((2, 1), targets);
+                helper.build_square_corners((1, 2), targets);
             }
             PieceKind::Queen => {
-                helper.build_cross(None, InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
-                helper.build_diag_cross(None, InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
+                helper.build_cross(None, targets);
+                helper.build_diag_cross(None, targets);
             }
             PieceKind::King => {
-                helper.build_cross(Some(1), InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
-                helper
-                    .build_diag_cross(Some(1), InclusionPolicy::EMPTY | InclusionPolicy::DIFFERENT);
+                helper.build_cross(Some(1), targets);
+                helper.build_diag_cross(Some(1), targets);
+                if !attacks {
+                    helper.build_castling();
+                }
+            }
+            PieceKind::Archbishop => {
+                helper.build_diag_cross(None, targets);
+                helper.build_square_corners((2, 1), targets);
+                helper.build_square_corners((1, 2), targets);
+            }
+            PieceKind::Chancellor => {
+                helper.build_cross(None, targets);
+                helper.build_square_corners((2, 1), targets);
+                helper.build_square_corners((1, 2), targets);
             }
         };
         res
     }
 
+    /// Moves the piece to `to` within `board`, returning the captured piece.
+    /// Fails if `to` isn't reachable or the variant's rules reject the resulting
+    /// position, such as when it leaves the own king attacked. `board` is left in
+    /// an unspecified state then.
     pub fn move_to(
         &mut self,
         board: &mut BoardState,
         to: GridPosition,
-        king_position: GridPosition,
-    ) -> Result<(), MoveError> {
-        let piece_kind = self.kind;
+    ) -> Result<Option<Piece>, MoveError> {
+        let rules = board.variant.rules();
         let moves = self.pseudo_moveset(board);
 
-        if !moves.contains(&to) {
+        if !moves.contains(&to) || !rules.allows_move(board, self, to) {
             return Err(MoveError::InvalidTarget);
         }
-        board.move_piece(self.position, to);
-        let opposite = match self.color {
-            PieceColor::Black => PieceColor::White,
-            PieceColor::White => PieceColor::Black,
-        };
-        let opp_attack_map = board.attack_map(opposite);
-        if opp_attack_map.contains(&king_position)
-            || (piece_kind == PieceKind::King && opp_attack_map.contains(&to))
-        {
+        let taken = board.move_piece(self.position, to);
+        rules.after_move(board, self, to, taken.as_ref());
+        if !rules.is_legal(board, self.color) {
             return Err(MoveError::WouldCheck);
         }
 
         self.position = to;
-        Ok(())
+        Ok(taken)
     }
 }
 
+/// The two squares a pawn advancing along `front` captures on.
+fn pawn_diagonals(front: (i8, i8)) -> [(i8, i8); 2] {
+    let (fx, fy) = front;
+    [1, -1].map(|side| (fx + side * fy.abs(), fy + side * fx.abs()))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum MoveError {
     InvalidOrigin,
     InvalidTarget,
     WouldCheck,
+    /// The pawn would reach the last rank without any piece to promote to.
+    NoPromotion,
+    /// The piece doesn't belong to the side to move.
+    WrongTurn,
+    GameOver,
 }
 
 struct MoveConstructor<'a> {
@@ -115,6 +213,13 @@ struct MoveConstructor<'a> {
     result: &'a mut Vec<GridPosition>,
 }
 impl MoveConstructor<'_> {
+    /// Adds `square` to the result. On wrapping boards several lines may reach
+    /// the same square, which is only kept once.
+    fn push(&mut self, square: GridPosition) {
+        if !self.result.contains(&square) {
+            self.result.push(square);
+        }
+    }
     fn build_cross(&mut self, max: Option<u16>, include: InclusionPolicy) {
         let old = self.front;
         self.front = (1, 0);
@@ -138,55 +243,128 @@ impl MoveConstructor<'_> {
         self.front = old;
     }
     fn build_straight_line(&mut self, max: Option<u16>, include: InclusionPolicy) {
-        let max = max.unwrap_or(512);
-        let squares = (1..=max).map_while(|i| {
-            self.start.try_add((
-                self.front.0 as i32 * i as i32,
-                self.front.1 as i32 * i as i32,
-            ))
-        });
-
-        for candidate in squares {
-            if self.board.query_square(candidate, self.square_flags) {
-                match self.board.state.get(&candidate) {
-                    Some(p) => {
-                        let same = p.color == self.piece_color;
-                        if same && include.contains(InclusionPolicy::SAME) {
-                            self.result.push(candidate);
-                        }
-                        if !same && include.contains(InclusionPolicy::DIFFERENT) {
-                            self.result.push(candidate);
-                        }
-                        break;
+        let mut candidate = self.start;
+        for _ in 0..max.unwrap_or(u16::MAX) {
+            match self.board.step(candidate, self.front) {
+                // On wrapping boards a line ends once it comes back around.
+                Some(next) if next != self.start => candidate = next,
+                _ => break,
+            }
+            // Holes and walls stop sliders like the board's edge does.
+            if !self.board.query_square(candidate, self.square_flags) {
+                break;
+            }
+            match self.board.state.get(&candidate) {
+                Some(p) => {
+                    let same = p.color == self.piece_color;
+                    if same && include.contains(InclusionPolicy::SAME) {
+                        self.push(candidate);
+                    }
+                    if !same && include.contains(InclusionPolicy::DIFFERENT) {
+                        self.push(candidate);
                     }
-                    None => {
-                        if include.contains(InclusionPolicy::EMPTY) {
-                            self.result.push(candidate);
-                        }
+                    break;
+                }
+                None => {
+                    if include.contains(InclusionPolicy::EMPTY) {
+                        self.push(candidate);
                     }
                 }
             }
         }
     }
 
-    fn build_square_corners(&mut self, delta: (i8, i8)) {
+    fn build_square_corners(&mut self, delta: (i8, i8), include: InclusionPolicy) {
         for m1 in [-1, 1] {
             for m2 in [-1, 1] {
                 let delta = (delta.0 * m1, delta.1 * m2);
-                if let Some(candidate) = self.start.try_add(delta) {
-                    if self.board.query_square(candidate, self.square_flags)
-                        && self
-                            .board
-                            .state
-                            .get(&candidate)
-                            .is_none_or(|p| p.color != self.piece_color)
-                    {
-                        self.result.push(candidate);
+                if let Some(candidate) = self.board.step(self.start, delta)
+                    && self.board.query_square(candidate, self.square_flags)
+                {
+                    let policy = match self.board.state.get(&candidate) {
+                        Some(p) if p.color == self.piece_color => InclusionPolicy::SAME,
+                        Some(_) => InclusionPolicy::DIFFERENT,
+                        None => InclusionPolicy::EMPTY,
+                    };
+                    if include.contains(policy) {
+                        self.push(candidate);
                     }
                 }
             }
         }
     }
+    /// Pushes the squares of the rooks the king may castle with.
+    /// Every square between the king, the rook and their destinations has to be
+    /// empty and, in variants with check, the king may not start, pass through
+    /// or land on an attacked square.
+    fn build_castling(&mut self) {
+        let rank = match self.piece_color {
+            PieceColor::Black => self.board.num_cells.y - 1,
+            PieceColor::White => 0,
+            PieceColor::Blue | PieceColor::Green => return,
+        };
+        if self.start.y != rank {
+            return;
+        }
+        let rook_files: Vec<u16> = self.board.castling.rook_files(self.piece_color).collect();
+        if rook_files.is_empty() {
+            return;
+        }
+        let attacked = if self.board.variant.rules().has_check() {
+            self.board.attacked_by(self.piece_color.opposite())
+        } else {
+            vec![]
+        };
+        let span = |a: u16, b: u16| a.min(b)..=a.max(b);
+
+        for rook_file in rook_files {
+            let rook = GridPosition {
+                x: rook_file,
+                y: rank,
+            };
+            if !self
+                .board
+                .state
+                .get(&rook)
+                .is_some_and(|p| p.kind == PieceKind::Rook && p.color == self.piece_color)
+            {
+                continue;
+            }
+            let side = CastlingSide::of(self.start.x, rook_file);
+            let (king_to, rook_to) = side.destinations(self.board.num_cells.x);
+            let blocked = span(self.start.x, king_to)
+                .chain(span(rook_file, rook_to))
+                .filter(|&x| x != self.start.x && x != rook_file)
+                .map(|x| GridPosition { x, y: rank })
+                .any(|p| {
+                    self.board.state.contains_key(&p)
+                        || !self.board.query_square(p, self.square_flags)
+                });
+            let checked = span(self.start.x, king_to)
+                .any(|x| attacked.contains(&GridPosition { x, y: rank }));
+            if !blocked && !checked {
+                self.push(rook);
+            }
+        }
+    }
+    /// Pushes the square in `front` if it is the en passant target and the
+    /// pawn that skipped it belongs to the opponent.
+    fn build_en_passant(&mut self) {
+        let Some(target) = self.board.en_passant else {
+            return;
+        };
+        if self.board.step(self.start, self.front) != Some(target) {
+            return;
+        }
+        let (fx, fy) = self.piece_color.front();
+        let passed = self
+            .board
+            .step(self.start, (self.front.0 - fx, self.front.1 - fy))
+            .and_then(|p| self.board.state.get(&p));
+        if passed.is_some_and(|p| p.kind == PieceKind::Pawn && p.color != self.piece_color) {
+            self.push(target);
+        }
+    }
 }
 bitflags::bitflags! {
     #[derive(Clone, Copy)]
@@ -207,6 +385,10 @@ impl PieceKind {
             knight,
             king,
             queen,
+            archbishop,
+            chancellor,
+            duck: _,
+            black_row: _,
         } = *map;
         match self {
             PieceKind::Pawn => pawn,
@@ -215,6 +397,8 @@ impl PieceKind {
             PieceKind::Knight => knight,
             PieceKind::Queen => queen,
             PieceKind::King => king,
+            PieceKind::Archbishop => archbishop,
+            PieceKind::Chancellor => chancellor,
         }
     }
 }
@@ -1,9 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use macroquad::{
-    color::{RED, WHITE},
-    math::{Rect, Vec2},
-    texture::{DrawTextureParams, draw_texture_ex},
+    color::{Color, LIME, RED, SKYBLUE, WHITE},
+    math::Vec2,
 };
 
 use super::{
@@ -12,203 +11,304 @@ use super::{
     sprites::SpritesMap,
 };
 
+/// Sprites and tint `color` is drawn with. Sides other than Black and White
+/// reuse White's sprites, tinted.
+pub fn sprites_for(
+    color: PieceColor,
+    black_sprites: &SpritesMap,
+    white_sprites: &SpritesMap,
+) -> (SpritesMap, Color) {
+    match color {
+        PieceColor::Black => (black_sprites.clone(), WHITE),
+        PieceColor::White => (white_sprites.clone(), WHITE),
+        PieceColor::Blue => (white_sprites.clone(), SKYBLUE),
+        PieceColor::Green => (white_sprites.clone(), LIME),
+    }
+}
+
+/// Every side of the game, in turn order.
+#[derive(Clone)]
 pub struct Players {
-    black: Player,
-    white: Player,
+    players: Vec<Player>,
 }
 impl Players {
-    pub fn new(black_sprites: SpritesMap, white_sprites: SpritesMap) -> Self {
-        let mut pieces = vec![];
-        for (idx, kind) in [
-            PieceKind::Rook,
-            PieceKind::Bishop,
-            PieceKind::Pawn,
-            PieceKind::Knight,
-            PieceKind::King,
-            PieceKind::Queen,
-        ]
-        .into_iter()
-        .enumerate()
-        {
-            let position = GridPosition {
-                x: idx as u16,
-                y: 2,
-            };
-            pieces.push(Piece {
-                kind,
-                color: PieceColor::White,
-                position,
-            });
+    /// Creates a player for each of `colors`, in turn order.
+    pub fn new(
+        colors: &[PieceColor],
+        pieces: Vec<Piece>,
+        black_sprites: SpritesMap,
+        white_sprites: SpritesMap,
+    ) -> Self {
+        let players = colors
+            .iter()
+            .map(|&color| {
+                let (sprites, tint) = sprites_for(color, &black_sprites, &white_sprites);
+                let pieces: Vec<Piece> = pieces
+                    .iter()
+                    .filter(|p| p.color == color)
+                    .cloned()
+                    .collect();
+                let mut player = Player::new(color, sprites, tint);
+                player.append_pieces(pieces);
+                player
+            })
+            .collect();
+        Self { players }
+    }
+    fn player(&self, color: PieceColor) -> &Player {
+        self.players
+            .iter()
+            .find(|p| p.color == color)
+            .expect("Every color of the variant has a player")
+    }
+    fn player_mut(&mut self, color: PieceColor) -> &mut Player {
+        self.players
+            .iter_mut()
+            .find(|p| p.color == color)
+            .expect("Every color of the variant has a player")
+    }
+    /// Sides still in the game, in turn order.
+    pub fn active(&self) -> impl Iterator<Item = PieceColor> + '_ {
+        self.players
+            .iter()
+            .filter(|p| !p.eliminated)
+            .map(|p| p.color)
+    }
+    /// The side still in the game playing after `color`, and whether the turn
+    /// order wrapped around to get there.
+    pub fn next_after(&self, color: PieceColor) -> (PieceColor, bool) {
+        let idx = self
+            .players
+            .iter()
+            .position(|p| p.color == color)
+            .unwrap_or_default();
+        let len = self.players.len();
+        (1..=len)
+            .map(|offset| (idx + offset) % len)
+            .find(|&next| !self.players[next].eliminated)
+            .map(|next| (self.players[next].color, next <= idx))
+            .unwrap_or((color, true))
+    }
+    /// Takes `color` out of the game, removing its pieces from the board.
+    pub fn eliminate(&mut self, color: PieceColor) {
+        let player = self.player_mut(color);
+        player.eliminated = true;
+        player.pieces.clear();
+        player.pocket.clear();
+    }
+    pub fn is_eliminated(&self, color: PieceColor) -> bool {
+        self.player(color).eliminated
+    }
+    pub fn score(&self, color: PieceColor) -> u32 {
+        self.player(color).score
+    }
+    pub fn add_score(&mut self, color: PieceColor, points: u32) {
+        self.player_mut(color).score += points;
+    }
+    pub fn sprites(&self, color: PieceColor) -> &SpritesMap {
+        &self.player(color).sprites
+    }
+    /// Swaps the piece set every side is drawn with.
+    pub fn set_sprites(&mut self, black_sprites: &SpritesMap, white_sprites: &SpritesMap) {
+        for player in &mut self.players {
+            (player.sprites, player.tint) = sprites_for(player.color, black_sprites, white_sprites);
         }
-        let mut white = Player::new(
-            GridPosition { x: 0, y: 0 },
-            PieceColor::White,
-            white_sprites,
-        );
-        white.append_pieces(pieces);
-        let mut black = Player::new(
-            GridPosition { x: 7, y: 7 },
-            PieceColor::Black,
-            black_sprites,
-        );
-        black.append_pieces(vec![Piece {
-            kind: PieceKind::Rook,
-            color: PieceColor::Black,
-            position: GridPosition { x: 3, y: 3 },
-        }]);
-        Self { black, white }
-    }
-    pub fn king_position(&self, color: PieceColor) -> GridPosition {
-        match color {
-            PieceColor::Black => self.black.king_position,
-            PieceColor::White => self.white.king_position,
+    }
+    /// Kinds of the pieces `color` has had captured, in capture order.
+    pub fn lost(&self, color: PieceColor) -> &[PieceKind] {
+        &self.player(color).lost
+    }
+    /// Pieces `color` holds in hand, ready to be dropped.
+    pub fn pocket(&self, color: PieceColor) -> &[PieceKind] {
+        &self.player(color).pocket
+    }
+    /// Number of times `color` has given check.
+    pub fn checks_given(&self, color: PieceColor) -> u32 {
+        self.player(color).checks_given
+    }
+    pub fn add_check(&mut self, color: PieceColor) {
+        self.player_mut(color).checks_given += 1;
+    }
+    pub fn add_to_pocket(&mut self, color: PieceColor, kind: PieceKind) {
+        self.player_mut(color).pocket.push(kind);
+    }
+    /// Takes one `kind` out of `color`'s pocket, returns whether there was one.
+    pub fn take_from_pocket(&mut self, color: PieceColor, kind: PieceKind) -> bool {
+        let pocket = &mut self.player_mut(color).pocket;
+        match pocket.iter().position(|k| *k == kind) {
+            Some(idx) => {
+                pocket.swap_remove(idx);
+                true
+            }
+            None => false,
         }
     }
+    /// Puts `piece` on the board, as done when dropping.
+    pub fn place(&mut self, piece: Piece) {
+        self.player_mut(piece.color).pieces.push(piece);
+    }
     pub fn piece_at(&self, position: GridPosition) -> Option<&Piece> {
-        self.black
-            .pieces
+        self.players
             .iter()
-            .chain(self.white.pieces.iter())
+            .flat_map(|p| p.pieces.iter())
             .find(|p| p.position == position)
     }
     fn piece_at_mut(&mut self, position: GridPosition) -> Option<&mut Piece> {
-        self.black
-            .pieces
+        self.players
             .iter_mut()
-            .chain(self.white.pieces.iter_mut())
+            .flat_map(|p| p.pieces.iter_mut())
             .find(|p| p.position == position)
     }
-    pub fn remove_piece_at(&mut self, position: GridPosition) -> Option<Piece> {
-        if let Some(idx) = self
-            .black
-            .pieces
-            .iter()
-            .position(|p| p.position == position)
-        {
-            return Some(self.black.pieces.swap_remove(idx));
-        };
-        if let Some(idx) = self
-            .white
-            .pieces
-            .iter()
-            .position(|p| p.position == position)
-        {
-            return Some(self.white.pieces.swap_remove(idx));
-        };
-        None
-    }
-
     pub fn pieces(&self) -> HashMap<GridPosition, Piece> {
-        self.black
-            .pieces
+        self.players
             .iter()
-            .chain(self.white.pieces.iter())
+            .flat_map(|p| p.pieces.iter())
             .map(|p| (p.position, p.clone()))
             .collect()
     }
 
+    /// Moves the piece at `from` to `to`, returning the captured piece, if any.
+    /// The move is validated and played on `snapshot`, whose resulting layout
+    /// then replaces the players' pieces.
     pub fn move_piece(
         &mut self,
         mut snapshot: BoardState,
         from: GridPosition,
         to: GridPosition,
-    ) -> Result<(), MoveError> {
-        let bk = self.black.king_position;
-        let wk = self.white.king_position;
-
-        if self.piece_at(to).is_some() {
-            self.remove_piece_at(to);
-        }
-        let piece = self.piece_at_mut(from).ok_or(MoveError::InvalidOrigin)?;
+    ) -> Result<Option<Piece>, MoveError> {
+        let mut piece = self.piece_at(from).ok_or(MoveError::InvalidOrigin)?.clone();
+        let captured = piece.move_to(&mut snapshot, to)?;
 
-        let king_position = match piece.color {
-            PieceColor::Black => bk,
-            PieceColor::White => wk,
-        };
-        piece.move_to(&mut snapshot, to, king_position)?;
-
-        if piece.kind == PieceKind::King {
-            match piece.color {
-                PieceColor::Black => self.black.king_position = to,
-                PieceColor::White => self.white.king_position = to,
+        for player in self.players.iter_mut() {
+            let color = player.color;
+            let pieces: Vec<Piece> = snapshot
+                .pieces()
+                .filter(|p| p.color == color)
+                .cloned()
+                .collect();
+            // Pieces gone from the board are lost, whether captured or caught
+            // in an Atomic explosion.
+            let mut kept: Vec<PieceKind> = pieces.iter().map(|p| p.kind).collect();
+            for before in &player.pieces {
+                match kept.iter().position(|&kind| kind == before.kind) {
+                    Some(idx) => {
+                        kept.swap_remove(idx);
+                    }
+                    None => player.lost.push(before.kind),
+                }
             }
+            player.pieces = pieces;
         }
 
-        Ok(())
+        Ok(captured)
+    }
+    /// Replaces the piece at `position` by one of kind `kind`.
+    pub fn promote(&mut self, position: GridPosition, kind: PieceKind) {
+        if let Some(piece) = self.piece_at_mut(position) {
+            piece.kind = kind;
+            piece.promoted = true;
+        }
     }
 
-    pub fn draw(&self, y_columns: u16, cell_size: Vec2, highlight_piece: Option<GridPosition>) {
-        for player in [&self.black, &self.white] {
-            player.draw(y_columns, cell_size, highlight_piece);
+    /// Draws every piece, or only those standing on `visible` squares when given,
+    /// with the top left corner of each at `world_of` its square. Pieces on
+    /// `hidden` squares are skipped, as they are being drawn elsewhere.
+    pub fn draw(
+        &self,
+        world_of: impl Fn(GridPosition) -> Vec2,
+        cell_size: Vec2,
+        highlight_piece: Option<GridPosition>,
+        visible: Option<&HashSet<GridPosition>>,
+        hidden: &[GridPosition],
+    ) {
+        for player in self.players.iter() {
+            player.draw(&world_of, cell_size, highlight_piece, visible, hidden);
         }
     }
+    /// Draws the piece at `position` with its top left corner at `world`
+    /// instead of in its square.
+    pub fn draw_piece_at(
+        &self,
+        position: GridPosition,
+        world: Vec2,
+        cell_size: Vec2,
+        highlight_piece: Option<GridPosition>,
+    ) {
+        let Some(piece) = self.piece_at(position) else {
+            return;
+        };
+        self.player(piece.color)
+            .draw_piece(piece, world, cell_size, highlight_piece);
+    }
 }
 
+/// One side's army. A side is royal when it has a king, sides without one,
+/// such as White in Horde, can't be checked and lose by being wiped out.
+#[derive(Clone)]
 pub struct Player {
-    king_position: GridPosition,
+    color: PieceColor,
     pieces: Vec<Piece>,
+    lost: Vec<PieceKind>,
+    pocket: Vec<PieceKind>,
+    checks_given: u32,
+    /// Points earned by capturing and eliminating, see [`super::rules::piece_points`].
+    score: u32,
+    eliminated: bool,
     sprites: SpritesMap,
+    tint: Color,
 }
 impl Player {
-    pub fn new(king_position: GridPosition, color: PieceColor, sprites: SpritesMap) -> Self {
+    pub fn new(color: PieceColor, sprites: SpritesMap, tint: Color) -> Self {
         Self {
-            king_position,
-            pieces: vec![Piece {
-                kind: PieceKind::King,
-                color,
-                position: king_position,
-            }],
+            color,
+            pieces: vec![],
+            lost: vec![],
+            pocket: vec![],
+            checks_given: 0,
+            score: 0,
+            eliminated: false,
             sprites,
+            tint,
         }
     }
     pub fn append_pieces(&mut self, pieces: Vec<Piece>) {
-        let color = pieces.first().map(|p| p.color);
-        if color.is_none() {
-            return;
-        }
-        let color = color.unwrap();
         self.pieces.reserve_exact(pieces.len());
         for mut p in pieces {
-            if p.kind == PieceKind::King {
-                self.remove_piece_at(self.king_position);
-                self.king_position = p.position;
-            }
-            p.color = color;
+            p.color = self.color;
             self.pieces.push(p);
         }
     }
-    pub fn remove_piece_at(&mut self, position: GridPosition) -> Option<Piece> {
-        if let Some(idx) = self.pieces.iter().position(|p| p.position == position) {
-            return Some(self.pieces.swap_remove(idx));
-        };
-        None
-    }
 
-    pub fn draw(&self, y_columns: u16, cell_size: Vec2, highlight_piece: Option<GridPosition>) {
+    pub fn draw(
+        &self,
+        world_of: impl Fn(GridPosition) -> Vec2,
+        cell_size: Vec2,
+        highlight_piece: Option<GridPosition>,
+        visible: Option<&HashSet<GridPosition>>,
+        hidden: &[GridPosition],
+    ) {
         for piece in self.pieces.iter() {
-            let GridPosition { x, y } = piece.position;
-            let y = y_columns - y - 1;
-            let GridPosition { x: ax, y: ay } = piece.kind.atlas_offset(&self.sprites.mappings);
-            let modulate = if highlight_piece.is_some_and(|p| p == piece.position) {
-                RED
-            } else {
-                WHITE
-            };
-            draw_texture_ex(
-                &self.sprites.atlas,
-                x as f32 * cell_size.x,
-                y as f32 * cell_size.y,
-                modulate,
-                DrawTextureParams {
-                    source: Some(Rect {
-                        x: ax as f32 * cell_size.x,
-                        y: ay as f32 * cell_size.y,
-                        w: cell_size.x,
-                        h: cell_size.y,
-                    }),
-                    ..Default::default()
-                },
-            );
+            if visible.is_some_and(|v| !v.contains(&piece.position))
+                || hidden.contains(&piece.position)
+            {
+                continue;
+            }
+            self.draw_piece(piece, world_of(piece.position), cell_size, highlight_piece);
         }
     }
+    fn draw_piece(
+        &self,
+        piece: &Piece,
+        world: Vec2,
+        cell_size: Vec2,
+        highlight_piece: Option<GridPosition>,
+    ) {
+        let modulate = if highlight_piece.is_some_and(|p| p == piece.position) {
+            RED
+        } else {
+            self.tint
+        };
+        self.sprites
+            .draw_piece(piece.kind, world, cell_size, modulate);
+    }
 }
@@ -0,0 +1,668 @@
+use std::fmt::Display;
+
+use super::{
+    Board, BoardState, GridPosition,
+    piece::{Piece, PieceColor, PieceKind},
+};
+
+/// Hooks for variants that keep the normal movement rules but change what
+/// happens after a move or how a game is decided.
+/// Rules are stateless, anything they need to remember lives in the board.
+pub trait VariantRules: Sync {
+    /// Whether `piece` may make the pseudolegal move to `to`, before playing it.
+    fn allows_move(&self, _state: &BoardState, _piece: &Piece, _to: GridPosition) -> bool {
+        true
+    }
+    /// Side effects of a move, applied to `state` right after `piece` has moved
+    /// to `to` capturing `captured`.
+    fn after_move(
+        &self,
+        _state: &mut BoardState,
+        _piece: &Piece,
+        _to: GridPosition,
+        _captured: Option<&Piece>,
+    ) {
+    }
+    /// Whether kings can be in check at all. Without check, a king may also
+    /// castle out of, through or into an attack.
+    fn has_check(&self) -> bool {
+        true
+    }
+    fn in_check(&self, state: &mut BoardState, color: PieceColor) -> bool {
+        let Some(king) = state.king_position(color) else {
+            return false;
+        };
+        state
+            .enemies(color)
+            .any(|enemy| state.attack_map(enemy).contains(&king))
+    }
+    /// Whether the position reached after `color` moved is legal.
+    fn is_legal(&self, state: &mut BoardState, color: PieceColor) -> bool {
+        !self.in_check(state, color)
+    }
+    /// The outcome of the game, `None` while it goes on.
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        standard_result(self, board)
+    }
+}
+
+/// Checkmate, stalemate and the fifty-move rule.
+/// A side without a king loses once all of its pieces are captured.
+pub fn standard_result<R: VariantRules + ?Sized>(rules: &R, board: &Board) -> Option<GameResult> {
+    let turn = board.turn();
+    let state = board.snapshot();
+    for color in [PieceColor::White, PieceColor::Black] {
+        if state.king_position(color).is_none() && !state.pieces().any(|p| p.color == color) {
+            return Some(GameResult::Win {
+                winner: color.opposite(),
+                reason: WinReason::WipedOut,
+            });
+        }
+    }
+    if !board.has_legal_moves() {
+        return Some(if rules.in_check(&mut board.snapshot(), turn) {
+            GameResult::Win {
+                winner: turn.opposite(),
+                reason: WinReason::Checkmate,
+            }
+        } else {
+            GameResult::Draw(DrawReason::Stalemate)
+        });
+    }
+    if board.halfmove_clock >= 100 {
+        return Some(GameResult::Draw(DrawReason::FiftyMoves));
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    Win {
+        winner: PieceColor,
+        reason: WinReason,
+    },
+    Draw(DrawReason),
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinReason {
+    Checkmate,
+    KingExploded,
+    KingOfTheHill,
+    ThreeChecks,
+    /// The loser had no king and lost every piece.
+    WipedOut,
+    /// Fog of war, the loser's king was captured.
+    KingCaptured,
+    /// Four-player chess, every other side was eliminated.
+    LastKingStanding,
+    /// Four-player chess, the winner scored the most points.
+    Points,
+    /// Antichess, the winner has no pieces left.
+    LostAllPieces,
+    /// Antichess and Duck chess, the winner has pieces left but can't move them.
+    Stalemated,
+    /// The loser ran out of time.
+    Timeout,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMoves,
+    /// Four-player chess, several sides share the highest score.
+    TiedPoints,
+    /// One side ran out of time, but the other couldn't have mated anyway.
+    TimeoutVsInsufficientMaterial,
+}
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Win { winner, reason } => write!(f, "{winner:?} wins by {reason:?}"),
+            GameResult::Draw(reason) => write!(f, "Draw by {reason:?}"),
+        }
+    }
+}
+
+/// Whether a side with `pieces` and `pocket` could ever checkmate. A lone
+/// king, or a king with a single bishop or knight, can't.
+pub fn has_mating_material<'a>(
+    pieces: impl Iterator<Item = &'a Piece>,
+    pocket: &[PieceKind],
+) -> bool {
+    if !pocket.is_empty() {
+        return true;
+    }
+    let mut minors = 0;
+    for piece in pieces {
+        match piece.kind {
+            PieceKind::King => (),
+            PieceKind::Bishop | PieceKind::Knight => minors += 1,
+            _ => return true,
+        }
+    }
+    minors > 1
+}
+
+pub struct Standard;
+impl VariantRules for Standard {}
+
+/// Captures explode, removing the capturer and every piece but pawns around
+/// the captured square. Blowing up the enemy king wins, kings can't capture and
+/// adjacent kings can't check each other.
+pub struct Atomic;
+impl Atomic {
+    fn kings_touch(state: &BoardState) -> bool {
+        match (
+            state.king_position(PieceColor::White),
+            state.king_position(PieceColor::Black),
+        ) {
+            (Some(w), Some(b)) => w.x.abs_diff(b.x) <= 1 && w.y.abs_diff(b.y) <= 1,
+            _ => false,
+        }
+    }
+}
+impl VariantRules for Atomic {
+    fn allows_move(&self, state: &BoardState, piece: &Piece, to: GridPosition) -> bool {
+        piece.kind != PieceKind::King || state.piece_at(to).is_none_or(|p| p.color == piece.color)
+    }
+    fn after_move(
+        &self,
+        state: &mut BoardState,
+        _piece: &Piece,
+        to: GridPosition,
+        captured: Option<&Piece>,
+    ) {
+        if captured.is_none() {
+            return;
+        }
+        state.remove(to);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if let Some(p) = state.step(to, (dx, dy))
+                    && state.piece_at(p).is_some_and(|p| p.kind != PieceKind::Pawn)
+                {
+                    state.remove(p);
+                }
+            }
+        }
+    }
+    fn in_check(&self, state: &mut BoardState, color: PieceColor) -> bool {
+        !Self::kings_touch(state) && Standard.in_check(state, color)
+    }
+    fn is_legal(&self, state: &mut BoardState, color: PieceColor) -> bool {
+        if state.king_position(color).is_none() {
+            return false;
+        }
+        state.king_position(color.opposite()).is_none() || !self.in_check(state, color)
+    }
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        let state = board.snapshot();
+        for color in [PieceColor::White, PieceColor::Black] {
+            if state.king_position(color).is_none() {
+                return Some(GameResult::Win {
+                    winner: color.opposite(),
+                    reason: WinReason::KingExploded,
+                });
+            }
+        }
+        standard_result(self, board)
+    }
+}
+
+/// Bringing the king to one of the central squares wins.
+pub struct KingOfTheHill;
+impl VariantRules for KingOfTheHill {
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        let state = board.snapshot();
+        let (w, h) = (board.num_cells.x, board.num_cells.y);
+        let hill = |p: GridPosition| {
+            (w.saturating_sub(1) / 2..=w / 2).contains(&p.x)
+                && (h.saturating_sub(1) / 2..=h / 2).contains(&p.y)
+        };
+        for color in [PieceColor::White, PieceColor::Black] {
+            if state.king_position(color).is_some_and(hill) {
+                return Some(GameResult::Win {
+                    winner: color,
+                    reason: WinReason::KingOfTheHill,
+                });
+            }
+        }
+        standard_result(self, board)
+    }
+}
+
+/// Giving check for the third time wins.
+pub struct ThreeCheck;
+impl VariantRules for ThreeCheck {
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        for color in [PieceColor::White, PieceColor::Black] {
+            if board.players.checks_given(color) >= 3 {
+                return Some(GameResult::Win {
+                    winner: color,
+                    reason: WinReason::ThreeChecks,
+                });
+            }
+        }
+        standard_result(self, board)
+    }
+}
+
+/// Losing chess. Capturing is compulsory, kings are ordinary pieces that can be
+/// captured and left attacked, and a side wins by running out of moves, either by
+/// losing every piece or by being stalemated.
+pub struct Antichess;
+impl Antichess {
+    fn can_capture(state: &BoardState, color: PieceColor) -> bool {
+        state.pieces().filter(|p| p.color == color).any(|p| {
+            p.pseudo_moveset(state)
+                .into_iter()
+                .any(|to| state.is_capture(p, to))
+        })
+    }
+}
+impl VariantRules for Antichess {
+    fn allows_move(&self, state: &BoardState, piece: &Piece, to: GridPosition) -> bool {
+        state.is_capture(piece, to) || !Self::can_capture(state, piece.color)
+    }
+    fn has_check(&self) -> bool {
+        false
+    }
+    fn in_check(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        false
+    }
+    fn is_legal(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        true
+    }
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        let turn = board.turn();
+        if board.has_legal_moves() {
+            return (board.halfmove_clock >= 100)
+                .then_some(GameResult::Draw(DrawReason::FiftyMoves));
+        }
+        let reason = if board.snapshot().pieces().any(|p| p.color == turn) {
+            WinReason::Stalemated
+        } else {
+            WinReason::LostAllPieces
+        };
+        Some(GameResult::Win {
+            winner: turn,
+            reason,
+        })
+    }
+}
+
+/// Dark chess. Each side only sees the squares its pieces can reach, there is
+/// no check and the game is won by capturing the king.
+pub struct FogOfWar;
+impl VariantRules for FogOfWar {
+    fn has_check(&self) -> bool {
+        false
+    }
+    fn in_check(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        false
+    }
+    fn is_legal(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        true
+    }
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        king_captured(board).or_else(|| standard_result(self, board))
+    }
+}
+
+/// A win for the side that captured the enemy king, if one was.
+fn king_captured(board: &Board) -> Option<GameResult> {
+    let state = board.snapshot();
+    [PieceColor::White, PieceColor::Black]
+        .into_iter()
+        .find(|&color| state.king_position(color).is_none())
+        .map(|color| GameResult::Win {
+            winner: color.opposite(),
+            reason: WinReason::KingCaptured,
+        })
+}
+
+/// Duck chess. Every move is followed by moving the duck, a neutral blocker,
+/// to another empty square. There is no check, capturing the king wins and a
+/// stalemated side wins as well.
+pub struct Duck;
+impl VariantRules for Duck {
+    fn has_check(&self) -> bool {
+        false
+    }
+    fn in_check(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        false
+    }
+    fn is_legal(&self, _state: &mut BoardState, _color: PieceColor) -> bool {
+        true
+    }
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        if let Some(result) = king_captured(board) {
+            return Some(result);
+        }
+        if !board.has_legal_moves() {
+            return Some(GameResult::Win {
+                winner: board.turn(),
+                reason: WinReason::Stalemated,
+            });
+        }
+        (board.halfmove_clock >= 100).then_some(GameResult::Draw(DrawReason::FiftyMoves))
+    }
+}
+
+/// How a four-player game picks its winner once a single side is left.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scoring {
+    /// The side with the most points wins, survivor or not.
+    #[default]
+    Points,
+    /// The side left standing wins.
+    LastKingStanding,
+}
+
+/// Points for checkmating a side, or for being stalemated.
+pub const ELIMINATION_POINTS: u32 = 20;
+/// Points a capture of `piece` earns in four-player chess.
+pub fn piece_points(piece: &Piece) -> u32 {
+    if piece.promoted {
+        return 1;
+    }
+    match piece.kind {
+        PieceKind::Pawn => 1,
+        PieceKind::Knight => 3,
+        PieceKind::Bishop | PieceKind::Rook => 5,
+        PieceKind::Archbishop | PieceKind::Chancellor => 7,
+        PieceKind::Queen => 9,
+        PieceKind::King => 0,
+    }
+}
+
+/// Four-player free-for-all on the cross board. A side that can't move on its
+/// turn is eliminated by the board and the game goes on until one side is left.
+pub struct FourPlayer {
+    scoring: Scoring,
+}
+impl FourPlayer {
+    pub const POINTS: Self = Self {
+        scoring: Scoring::Points,
+    };
+    pub const LAST_KING_STANDING: Self = Self {
+        scoring: Scoring::LastKingStanding,
+    };
+}
+impl VariantRules for FourPlayer {
+    fn result(&self, board: &Board) -> Option<GameResult> {
+        let active: Vec<PieceColor> = board.players.active().collect();
+        if active.len() > 1 {
+            let limit = 50 * active.len() as u32;
+            return (board.halfmove_clock >= limit)
+                .then_some(GameResult::Draw(DrawReason::FiftyMoves));
+        }
+        match self.scoring {
+            Scoring::LastKingStanding => Some(GameResult::Win {
+                winner: *active.first()?,
+                reason: WinReason::LastKingStanding,
+            }),
+            Scoring::Points => {
+                let colors = board.variant.colors();
+                let best = colors.iter().map(|&c| board.players.score(c)).max()?;
+                let mut leaders = colors.iter().filter(|&&c| board.players.score(c) == best);
+                match (leaders.next(), leaders.next()) {
+                    (Some(&winner), None) => Some(GameResult::Win {
+                        winner,
+                        reason: WinReason::Points,
+                    }),
+                    _ => Some(GameResult::Draw(DrawReason::TiedPoints)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::variant::Variant;
+
+    fn square(name: &str) -> GridPosition {
+        name.parse().unwrap()
+    }
+    fn play(board: &mut Board, moves: &[(&str, &str)]) {
+        for (from, to) in moves {
+            board.try_move_piece(square(from), square(to)).unwrap();
+        }
+    }
+    fn win(winner: PieceColor, reason: WinReason) -> Option<GameResult> {
+        Some(GameResult::Win { winner, reason })
+    }
+
+    #[test]
+    fn atomic_explosions_spare_pawns() {
+        let mut board = Board::from_fen(Variant::Atomic, "k7/8/2rbn3/2pp4/8/8/8/3QK3 w - - 0 1");
+        play(&mut board, &[("d1", "d5")]);
+        for gone in ["d1", "d5", "c6", "d6", "e6"] {
+            assert!(board.piece_at(square(gone)).is_none(), "{gone}");
+        }
+        assert_eq!(board.piece_at(square("c5")).unwrap().kind, PieceKind::Pawn);
+        assert_eq!(board.players.lost(PieceColor::White), [PieceKind::Queen]);
+        let lost = board.players.lost(PieceColor::Black);
+        assert_eq!(lost.len(), 4);
+        for kind in [
+            PieceKind::Pawn,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+        ] {
+            assert!(lost.contains(&kind), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn atomic_kings() {
+        // Kings can't capture.
+        let board = Board::from_fen(Variant::Atomic, "4k3/8/8/8/8/8/3p4/4K3 w - - 0 1");
+        assert!(!board.legal_moves(square("e1")).contains(&square("d2")));
+        // Touching kings can't be checked.
+        let board = Board::from_fen(Variant::Atomic, "8/8/8/8/8/4k3/r3K3/8 w - - 0 1");
+        assert!(!board.in_check(PieceColor::White));
+        let board = Board::from_fen(Variant::Atomic, "8/8/8/8/4k3/8/r3K3/8 w - - 0 1");
+        assert!(board.in_check(PieceColor::White));
+    }
+
+    #[test]
+    fn atomic_exploding_the_king_wins() {
+        let mut board = Board::from_fen(Variant::Atomic, "4k3/4p3/8/8/8/8/8/4RK2 w - - 0 1");
+        play(&mut board, &[("e1", "e7")]);
+        assert_eq!(
+            board.result(),
+            win(PieceColor::White, WinReason::KingExploded)
+        );
+    }
+
+    #[test]
+    fn king_of_the_hill() {
+        let mut board = Board::from_fen(Variant::KingOfTheHill, "4k3/8/8/8/8/4K3/8/8 w - - 0 1");
+        play(&mut board, &[("e3", "d3")]);
+        assert_eq!(board.result(), None);
+        play(&mut board, &[("e8", "e7"), ("d3", "d4")]);
+        assert_eq!(
+            board.result(),
+            win(PieceColor::White, WinReason::KingOfTheHill)
+        );
+    }
+
+    #[test]
+    fn three_checks() {
+        let mut board = Board::from_fen(Variant::ThreeCheck, "k7/8/8/8/8/8/8/3Q3K w - - 0 1");
+        play(
+            &mut board,
+            &[("d1", "d5"), ("a8", "a7"), ("d5", "d4"), ("a7", "a8")],
+        );
+        assert_eq!(board.players.checks_given(PieceColor::White), 2);
+        assert_eq!(board.result(), None);
+        play(&mut board, &[("d4", "d5")]);
+        assert_eq!(
+            board.result(),
+            win(PieceColor::White, WinReason::ThreeChecks)
+        );
+    }
+
+    #[test]
+    fn antichess_captures_are_compulsory() {
+        let board = Board::from_fen(Variant::Antichess, "8/8/8/3p4/4P3/8/8/R7 w - - 0 1");
+        assert!(board.legal_moves(square("a1")).is_empty());
+        assert_eq!(board.legal_moves(square("e4")), [square("d5")]);
+        // Without captures, any move goes.
+        let board = Board::from_fen(Variant::Antichess, "8/8/8/8/3p4/8/4P3/R7 w - - 0 1");
+        assert_eq!(board.legal_moves(square("a1")).len(), 14);
+    }
+
+    #[test]
+    fn antichess_losing_every_piece_wins() {
+        let mut board = Board::from_fen(Variant::Antichess, "8/8/8/3p4/4P3/8/8/8 b - - 0 1");
+        play(&mut board, &[("d5", "e4")]);
+        assert_eq!(
+            board.result(),
+            win(PieceColor::White, WinReason::LostAllPieces)
+        );
+    }
+
+    #[test]
+    fn antichess_running_out_of_moves_wins() {
+        let mut board = Board::from_fen(Variant::Antichess, "8/8/8/4p3/8/4P3/8/8 b - - 0 1");
+        play(&mut board, &[("e5", "e4")]);
+        assert_eq!(
+            board.result(),
+            win(PieceColor::White, WinReason::Stalemated)
+        );
+    }
+
+    #[test]
+    fn horde_loses_when_wiped_out() {
+        let mut board = Board::from_fen(Variant::Horde, "4k3/8/8/8/8/8/3q4/4P3 b - - 0 1");
+        play(&mut board, &[("d2", "e1")]);
+        assert_eq!(board.result(), win(PieceColor::Black, WinReason::WipedOut));
+    }
+
+    #[test]
+    fn horde_wins_by_checkmate() {
+        let mut board = Board::from_fen(Variant::Horde, "7k/6pp/8/8/8/8/8/R7 w - - 0 1");
+        play(&mut board, &[("a1", "a8")]);
+        assert_eq!(board.result(), win(PieceColor::White, WinReason::Checkmate));
+    }
+
+    /// Kings in the arms of the cross, with `victim`'s in a corner of the left
+    /// arm where a white rook sliding to a11 mates it.
+    fn four_player(scoring: Scoring, victim: PieceColor) -> Board {
+        let variant = Variant::FourPlayer(scoring);
+        let piece = |kind, color, (x, y)| Piece {
+            kind,
+            color,
+            position: GridPosition { x, y },
+            promoted: false,
+        };
+        let mut position = variant.starting_position();
+        position.pieces = [
+            (PieceColor::White, (7, 0)),
+            (PieceColor::Blue, (12, 4)),
+            (PieceColor::Black, (7, 13)),
+            (PieceColor::Green, (12, 8)),
+        ]
+        .into_iter()
+        .map(|(color, at)| {
+            let at = if color == victim { (0, 3) } else { at };
+            piece(PieceKind::King, color, at)
+        })
+        .collect();
+        position
+            .pieces
+            .push(piece(PieceKind::Rook, PieceColor::White, (5, 10)));
+        position
+            .pieces
+            .push(piece(PieceKind::Rook, PieceColor::White, (1, 9)));
+        position.castling = Default::default();
+        Board::headless(variant, position)
+    }
+    type Step = ((u16, u16), (u16, u16));
+    fn play_at(board: &mut Board, moves: &[Step]) {
+        for &((fx, fy), (tx, ty)) in moves {
+            let from = GridPosition { x: fx, y: fy };
+            let to = GridPosition { x: tx, y: ty };
+            board.try_move_piece(from, to).unwrap();
+        }
+    }
+    const MATE: Step = ((5, 10), (0, 10));
+
+    #[test]
+    fn four_player_turns_skip_eliminated_sides() {
+        let mut board = four_player(Scoring::Points, PieceColor::Blue);
+        board.players.eliminate(PieceColor::Blue);
+        play_at(&mut board, &[((7, 0), (7, 1))]);
+        assert_eq!(board.turn(), PieceColor::Black);
+        play_at(&mut board, &[((7, 13), (7, 12))]);
+        assert_eq!(board.turn(), PieceColor::Green);
+        play_at(&mut board, &[((12, 8), (11, 8))]);
+        assert_eq!(board.turn(), PieceColor::White);
+    }
+
+    #[test]
+    fn four_player_checkmate_eliminates() {
+        let mut board = four_player(Scoring::Points, PieceColor::Blue);
+        play_at(&mut board, &[MATE]);
+        assert!(board.players.is_eliminated(PieceColor::Blue));
+        assert_eq!(board.players.score(PieceColor::White), ELIMINATION_POINTS);
+        assert_eq!(board.turn(), PieceColor::Black);
+        assert_eq!(board.result(), None);
+    }
+
+    #[test]
+    fn four_player_captures_score() {
+        let mut board = four_player(Scoring::Points, PieceColor::Blue);
+        let knight = Piece {
+            kind: PieceKind::Knight,
+            color: PieceColor::Black,
+            position: GridPosition { x: 1, y: 5 },
+            promoted: false,
+        };
+        board.players.place(knight);
+        play_at(&mut board, &[((1, 9), (1, 5))]);
+        assert_eq!(board.players.score(PieceColor::White), 3);
+    }
+
+    #[test]
+    fn four_player_last_king_standing() {
+        let mut board = four_player(Scoring::LastKingStanding, PieceColor::Black);
+        board.players.eliminate(PieceColor::Blue);
+        board.players.eliminate(PieceColor::Green);
+        play_at(&mut board, &[MATE]);
+        let reason = WinReason::LastKingStanding;
+        assert_eq!(board.result(), win(PieceColor::White, reason));
+    }
+
+    #[test]
+    fn four_player_points() {
+        let mut board = four_player(Scoring::Points, PieceColor::Black);
+        board.players.eliminate(PieceColor::Blue);
+        board.players.eliminate(PieceColor::Green);
+        play_at(&mut board, &[MATE]);
+        assert_eq!(board.result(), win(PieceColor::White, WinReason::Points));
+
+        // The points decide, not who is left.
+        let mut board = four_player(Scoring::Points, PieceColor::Black);
+        board.players.eliminate(PieceColor::Blue);
+        board.players.eliminate(PieceColor::Green);
+        board
+            .players
+            .add_score(PieceColor::Blue, 2 * ELIMINATION_POINTS);
+        play_at(&mut board, &[MATE]);
+        assert_eq!(board.result(), win(PieceColor::Blue, WinReason::Points));
+
+        let mut board = four_player(Scoring::Points, PieceColor::Black);
+        board.players.eliminate(PieceColor::Blue);
+        board.players.eliminate(PieceColor::Green);
+        board
+            .players
+            .add_score(PieceColor::Green, ELIMINATION_POINTS);
+        play_at(&mut board, &[MATE]);
+        let tied = Some(GameResult::Draw(DrawReason::TiedPoints));
+        assert_eq!(board.result(), tied);
+    }
+}
@@ -0,0 +1,139 @@
+use super::{
+    Board, GridPosition,
+    castling::CastlingSide,
+    piece::{Piece, PieceColor, PieceKind},
+};
+
+/// A move as listed in the game's history.
+#[derive(Clone, Debug)]
+pub struct PlayedMove {
+    pub color: PieceColor,
+    /// Full move the move was played in.
+    pub fullmove: u32,
+    /// Standard Algebraic Notation, with drops written as `N@e4` and the
+    /// duck's move appended as `,@e4`.
+    pub san: String,
+}
+
+/// Name of `p` in lowercase, as SAN writes it.
+pub fn square_name(p: GridPosition) -> String {
+    p.to_string().to_lowercase()
+}
+fn split_square(p: GridPosition) -> (String, String) {
+    let name = square_name(p);
+    let split = name
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(name.len());
+    let (file, rank) = name.split_at(split);
+    (file.to_string(), rank.to_string())
+}
+pub fn piece_letter(kind: PieceKind) -> char {
+    kind.fen_char(PieceColor::White)
+}
+
+/// SAN of `piece` moving to `to` on `board`, before the move is made and
+/// without the promotion and check suffixes. A king moving onto its own rook
+/// castles.
+pub fn move_san(board: &Board, piece: &Piece, to: GridPosition) -> String {
+    if piece.kind == PieceKind::King && board.piece_at(to).is_some_and(|p| p.color == piece.color) {
+        return match CastlingSide::of(piece.position.x, to.x) {
+            CastlingSide::King => "O-O",
+            CastlingSide::Queen => "O-O-O",
+        }
+        .to_string();
+    }
+    let capture = board.snapshot().is_capture(piece, to);
+    let mut san = String::new();
+    if piece.kind == PieceKind::Pawn {
+        if capture {
+            // Pawns advancing sideways capture across ranks rather than files.
+            let (file, rank) = split_square(piece.position);
+            san.push_str(match piece.color.front() {
+                (0, _) => &file,
+                _ => &rank,
+            });
+        }
+    } else {
+        san.push(piece_letter(piece.kind));
+        san.push_str(&disambiguation(board, piece, to));
+    }
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&square_name(to));
+    san
+}
+/// Shortest part of the origin square telling `piece` apart from the other
+/// pieces of its kind that could also move to `to`.
+fn disambiguation(board: &Board, piece: &Piece, to: GridPosition) -> String {
+    let rivals: Vec<GridPosition> = board
+        .players
+        .pieces()
+        .into_values()
+        .filter(|p| p.kind == piece.kind && p.color == piece.color && p.position != piece.position)
+        .filter(|p| board.legal_moves(p.position).contains(&to))
+        .map(|p| p.position)
+        .collect();
+    let (file, rank) = split_square(piece.position);
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|r| r.x != piece.position.x) {
+        file
+    } else if rivals.iter().all(|r| r.y != piece.position.y) {
+        rank
+    } else {
+        file + &rank
+    }
+}
+/// SAN of dropping `kind` on `to`.
+pub fn drop_san(kind: PieceKind, to: GridPosition) -> String {
+    format!("{}@{}", piece_letter(kind), square_name(to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::variant::Variant;
+
+    fn san(fen: &str, from: &str, to: &str) -> String {
+        let board = Board::from_fen(Variant::Standard, fen);
+        let piece = board.piece_at(from.parse().unwrap()).unwrap();
+        move_san(&board, piece, to.parse().unwrap())
+    }
+
+    #[test]
+    fn file_disambiguation() {
+        let fen = "4k3/8/8/8/8/8/4K3/R6R w - - 0 1";
+        assert_eq!(san(fen, "a1", "d1"), "Rad1");
+        assert_eq!(san(fen, "h1", "f1"), "Rhf1");
+    }
+
+    #[test]
+    fn rank_disambiguation() {
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a3"), "R1a3");
+        assert_eq!(san(fen, "a5", "a3"), "R5a3");
+    }
+
+    #[test]
+    fn square_disambiguation() {
+        let fen = "k7/8/8/8/8/2Q1Q3/8/4Q2K w - - 0 1";
+        assert_eq!(san(fen, "e3", "d2"), "Qe3d2");
+        assert_eq!(san(fen, "c3", "d2"), "Qcd2");
+        assert_eq!(san(fen, "e1", "d2"), "Q1d2");
+    }
+
+    #[test]
+    fn pinned_rival_does_not_count() {
+        // The knight on e5 is pinned to the king by the rook on e8.
+        let fen = "4r1k1/8/8/4N3/8/8/8/2N1K3 w - - 0 1";
+        assert_eq!(san(fen, "c1", "d3"), "Nd3");
+        let fen = "6k1/8/8/4N3/8/8/8/2N1K3 w - - 0 1";
+        assert_eq!(san(fen, "c1", "d3"), "Ncd3");
+    }
+
+    #[test]
+    fn pawn_capture_names_the_file() {
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4", "d5"), "exd5");
+    }
+}
@@ -0,0 +1,110 @@
+//! Starting setups, written like the shape files read by [`BoardShape`] with
+//! the pieces standing on their squares as FEN letters. Like FEN, only White
+//! and Black are told apart.
+
+use super::{
+    GridPosition,
+    fen::Position,
+    piece::{Piece, PieceKind},
+    shape::{BoardShape, SquareKind},
+};
+
+/// The shape and pieces of `position`, one line per rank starting from the
+/// last one.
+pub fn write(position: &Position) -> String {
+    let num_cells = position.num_cells();
+    let mut res = String::new();
+    for y in (0..num_cells.y).rev() {
+        for x in 0..num_cells.x {
+            let square = GridPosition { x, y };
+            let piece = position.pieces.iter().find(|p| p.position == square);
+            res.push(match (position.shape.kind(square), piece) {
+                (_, Some(piece)) => piece.kind.fen_char(piece.color),
+                (Some(SquareKind::Normal), None) => '.',
+                (Some(SquareKind::Wall), None) => '#',
+                (Some(SquareKind::Hole) | None, None) => '-',
+            });
+        }
+        res.push('\n');
+    }
+    res
+}
+
+/// Reads a setup back into its shape and pieces. Pieces stand on normal
+/// squares, everything else is parsed as in a shape file.
+pub fn parse(s: &str) -> Result<(BoardShape, Vec<Piece>), String> {
+    let shape: BoardShape = s
+        .chars()
+        .map(|c| match PieceKind::from_fen_char(c) {
+            Some(_) => '.',
+            None => c,
+        })
+        .collect::<String>()
+        .parse()
+        .map_err(|()| "Invalid setup file".to_string())?;
+    let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut pieces = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        let y = shape.num_cells().y - idx as u16 - 1;
+        for (x, c) in line.chars().enumerate() {
+            if let Some((kind, color)) = PieceKind::from_fen_char(c) {
+                pieces.push(Piece {
+                    kind,
+                    color,
+                    position: GridPosition { x: x as u16, y },
+                    promoted: false,
+                });
+            }
+        }
+    }
+    Ok((shape, pieces))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::{piece::PieceColor, variant::Variant};
+
+    fn placement(pieces: &[Piece]) -> Vec<(PieceKind, PieceColor, GridPosition)> {
+        let mut res: Vec<_> = pieces
+            .iter()
+            .map(|p| (p.kind, p.color, p.position))
+            .collect();
+        res.sort_by_key(|&(_, _, p)| (p.y, p.x));
+        res
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "\
+-.k.-
+.#.#.
+..P..
+--.--
+.RK..
+";
+        let (shape, pieces) = parse(text).unwrap();
+        assert_eq!(
+            shape.kind(GridPosition { x: 1, y: 3 }),
+            Some(SquareKind::Wall)
+        );
+        assert_eq!(
+            shape.kind(GridPosition { x: 0, y: 1 }),
+            Some(SquareKind::Hole)
+        );
+        let position = Position {
+            shape,
+            pieces,
+            ..Variant::Standard.starting_position()
+        };
+        assert_eq!(write(&position), text);
+
+        let mut position = Variant::Standard.starting_position();
+        position.shape = include_str!("../../../assets/shapes/walls.txt")
+            .parse()
+            .unwrap();
+        let (shape, pieces) = parse(&write(&position)).unwrap();
+        assert_eq!(shape, position.shape);
+        assert_eq!(placement(&pieces), placement(&position.pieces));
+    }
+}
@@ -0,0 +1,256 @@
+use std::str::FromStr;
+
+use macroquad::math::U16Vec2;
+
+use super::GridPosition;
+
+/// What a square of the board's bounding rectangle is made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SquareKind {
+    #[default]
+    Normal,
+    /// Not part of the board at all, left undrawn.
+    Hole,
+    /// Part of the board but impassable, drawn as a block.
+    Wall,
+}
+
+/// Which edges of the board are joined together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    #[default]
+    Flat,
+    /// The first and last files are adjacent.
+    Cylinder,
+    /// Both the files and the ranks wrap around.
+    Torus,
+}
+impl Topology {
+    pub fn wraps_files(self) -> bool {
+        matches!(self, Topology::Cylinder | Topology::Torus)
+    }
+    pub fn wraps_ranks(self) -> bool {
+        matches!(self, Topology::Torus)
+    }
+}
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "flat" => Ok(Topology::Flat),
+            "cylinder" => Ok(Topology::Cylinder),
+            "torus" => Ok(Topology::Torus),
+            _ => Err(format!("Unknown topology '{s}'")),
+        }
+    }
+}
+
+/// Layout of the squares within the board's bounding rectangle.
+/// Pieces can't stand on holes or walls, sliders stop before them and leapers
+/// can't land on them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardShape {
+    num_cells: U16Vec2,
+    /// Row major, starting from the first rank.
+    squares: Vec<SquareKind>,
+    topology: Topology,
+}
+impl BoardShape {
+    pub fn rectangle(num_cells: U16Vec2) -> Self {
+        Self {
+            num_cells,
+            squares: vec![SquareKind::Normal; num_cells.x as usize * num_cells.y as usize],
+            topology: Topology::Flat,
+        }
+    }
+    /// Rectangle with `cut`x`cut` holes in its corners, as used by four-player chess.
+    pub fn cross(num_cells: U16Vec2, cut: u16) -> Self {
+        let mut shape = Self::rectangle(num_cells);
+        let outer = |v: u16, len: u16| v < cut || v >= len - cut;
+        for y in 0..num_cells.y {
+            for x in 0..num_cells.x {
+                if outer(x, num_cells.x) && outer(y, num_cells.y) {
+                    shape.set(GridPosition { x, y }, SquareKind::Hole);
+                }
+            }
+        }
+        shape
+    }
+
+    #[inline]
+    pub fn num_cells(&self) -> U16Vec2 {
+        self.num_cells
+    }
+    #[inline]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+    /// Square reached by moving `delta` from `from`, wrapping around the edges
+    /// joined by the topology. `None` when it leaves the bounding rectangle.
+    pub fn step<T: Into<i32>>(&self, from: GridPosition, delta: (T, T)) -> Option<GridPosition> {
+        let wrap = |v: i32, len: u16, wraps: bool| {
+            if wraps {
+                Some(v.rem_euclid(len as i32) as u16)
+            } else {
+                (0..len as i32).contains(&v).then_some(v as u16)
+            }
+        };
+        Some(GridPosition {
+            x: wrap(
+                from.x as i32 + delta.0.into(),
+                self.num_cells.x,
+                self.topology.wraps_files(),
+            )?,
+            y: wrap(
+                from.y as i32 + delta.1.into(),
+                self.num_cells.y,
+                self.topology.wraps_ranks(),
+            )?,
+        })
+    }
+    fn index(&self, position: GridPosition) -> Option<usize> {
+        let GridPosition { x, y } = position;
+        (x < self.num_cells.x && y < self.num_cells.y)
+            .then(|| y as usize * self.num_cells.x as usize + x as usize)
+    }
+    /// Kind of the square at `position`, `None` outside the bounding rectangle.
+    pub fn kind(&self, position: GridPosition) -> Option<SquareKind> {
+        self.index(position).map(|idx| self.squares[idx])
+    }
+    pub fn set(&mut self, position: GridPosition, kind: SquareKind) {
+        if let Some(idx) = self.index(position) {
+            self.squares[idx] = kind;
+        }
+    }
+    /// Whether a piece may stand on `position`.
+    pub fn is_playable(&self, position: GridPosition) -> bool {
+        self.kind(position) == Some(SquareKind::Normal)
+    }
+}
+
+impl FromStr for BoardShape {
+    type Err = ();
+
+    /// Parses a shape drawn as text, one line per rank starting from the last
+    /// one. `.` is a square, `#` a wall and `-` or a space a hole. Lines are
+    /// padded with holes up to the longest one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().ok_or(())?;
+        let num_cells = U16Vec2 {
+            x: width as u16,
+            y: lines.len() as u16,
+        };
+        let mut shape = Self::rectangle(num_cells);
+        for (idx, line) in lines.iter().enumerate() {
+            let y = num_cells.y - idx as u16 - 1;
+            let mut chars = line.chars();
+            for x in 0..num_cells.x {
+                let kind = match chars.next() {
+                    Some('.') => SquareKind::Normal,
+                    Some('#') => SquareKind::Wall,
+                    Some('-' | ' ') | None => SquareKind::Hole,
+                    Some(_) => return Err(()),
+                };
+                shape.set(GridPosition { x, y }, kind);
+            }
+        }
+        Ok(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::app::board::{Board, fen::Position, variant::Variant};
+
+    const DONUT: &str = include_str!("../../../assets/shapes/donut.txt");
+    const WALLS: &str = include_str!("../../../assets/shapes/walls.txt");
+
+    fn square(name: &str) -> GridPosition {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn donut() {
+        let shape: BoardShape = DONUT.parse().unwrap();
+        assert_eq!(shape.num_cells(), U16Vec2 { x: 8, y: 8 });
+        assert_eq!(shape.topology(), Topology::Flat);
+        for hole in ["d4", "e4", "d5", "e5"] {
+            assert_eq!(shape.kind(square(hole)), Some(SquareKind::Hole), "{hole}");
+            assert!(!shape.is_playable(square(hole)), "{hole}");
+        }
+        for normal in ["a1", "c4", "f5", "d3", "e6", "h8"] {
+            assert!(shape.is_playable(square(normal)), "{normal}");
+        }
+    }
+
+    #[test]
+    fn walls() {
+        let shape: BoardShape = WALLS.parse().unwrap();
+        assert_eq!(shape.num_cells(), U16Vec2 { x: 8, y: 8 });
+        for wall in ["b4", "b5", "g4", "g5"] {
+            assert_eq!(shape.kind(square(wall)), Some(SquareKind::Wall), "{wall}");
+            assert!(!shape.is_playable(square(wall)), "{wall}");
+        }
+        assert!(shape.is_playable(square("b3")));
+        assert!(!shape.is_playable(GridPosition { x: 8, y: 0 }));
+    }
+
+    #[test]
+    fn sliders_stop_before_walls() {
+        let mut position: Position = "4k3/8/8/8/8/8/8/1R1BK3 w - - 0 1".parse().unwrap();
+        position.shape = WALLS.parse().unwrap();
+        let board = Board::headless(Variant::Standard, position);
+
+        let rook: HashSet<_> = board.legal_moves(square("b1")).into_iter().collect();
+        assert!(rook.contains(&square("b3")));
+        assert!(!rook.contains(&square("b4")));
+        assert!(!rook.contains(&square("b6")));
+
+        let bishop: HashSet<_> = board.legal_moves(square("d1")).into_iter().collect();
+        let expected = ["c2", "b3", "a4", "e2", "f3"].map(square).into();
+        assert_eq!(bishop, expected);
+    }
+
+    #[test]
+    fn steps_wrap_with_the_topology() {
+        let mut shape = BoardShape::rectangle(U16Vec2 { x: 8, y: 8 });
+        assert_eq!(shape.step(square("h1"), (1, 0)), None);
+        assert_eq!(shape.step(square("a8"), (0, 1)), None);
+        assert_eq!(shape.step(square("b2"), (1, 1)), Some(square("c3")));
+
+        shape.set_topology(Topology::Cylinder);
+        assert_eq!(shape.step(square("h1"), (1, 0)), Some(square("a1")));
+        assert_eq!(shape.step(square("a3"), (-2, 1)), Some(square("g4")));
+        assert_eq!(shape.step(square("a8"), (0, 1)), None);
+
+        shape.set_topology(Topology::Torus);
+        assert_eq!(shape.step(square("a8"), (0, 1)), Some(square("a1")));
+        assert_eq!(shape.step(square("a1"), (-1, -1)), Some(square("h8")));
+        assert_eq!(shape.step(square("d4"), (9, -9)), Some(square("e3")));
+    }
+
+    #[test]
+    fn sliders_wrap_around_a_cylinder() {
+        let fen = "4k3/8/8/8/8/8/8/5K1R w - - 0 1";
+        let mut position: Position = fen.parse().unwrap();
+        position.shape.set_topology(Topology::Cylinder);
+        let board = Board::headless(Variant::Standard, position);
+        let rook: HashSet<_> = board.legal_moves(square("h1")).into_iter().collect();
+        for reached in ["g1", "a1", "b1", "e1", "h2", "h8"] {
+            assert!(rook.contains(&square(reached)), "{reached}");
+        }
+        assert!(!rook.contains(&square("f1")));
+        assert!(!rook.contains(&square("h1")));
+
+        let board = Board::from_fen(Variant::Standard, fen);
+        assert!(!board.legal_moves(square("h1")).contains(&square("a1")));
+    }
+}
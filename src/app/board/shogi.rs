@@ -0,0 +1,206 @@
+use super::{
+    BoardState, GridPosition, SquareQueryFlags,
+    piece::{Piece, PieceColor, PieceKind},
+};
+
+/// Shogi-style promotion and drop rules, usable by any variant on any grid.
+/// Captured pieces change side and go to the capturer's pocket, demoted.
+#[derive(Clone, Copy, Debug)]
+pub struct ShogiRules {
+    /// Number of ranks at the far side of the board. A piece may promote on
+    /// any move that starts or ends within them.
+    pub zone: u16,
+    /// Pieces that may promote, with the piece each one becomes.
+    /// Every promoted piece has to be unique so that it can be demoted.
+    pub promotions: &'static [(PieceKind, PieceKind)],
+    /// A side can't have two unpromoted pawns on the same file.
+    pub nifu: bool,
+    /// Dropping a pawn can't give checkmate.
+    pub no_pawn_drop_mate: bool,
+}
+impl ShogiRules {
+    /// Piece `piece` may promote to when moving to `to`, and whether doing so is
+    /// optional. Promotion is compulsory when the piece couldn't move any more.
+    pub fn promotion(
+        &self,
+        state: &BoardState,
+        piece: &Piece,
+        to: GridPosition,
+    ) -> Option<(PieceKind, bool)> {
+        if piece.promoted {
+            return None;
+        }
+        let (_, promoted) = self.promotions.iter().find(|(k, _)| *k == piece.kind)?;
+        let in_zone = |p| {
+            piece.color.relative_rank(p, state.num_cells) + self.zone
+                >= piece.color.depth(state.num_cells)
+        };
+        if !in_zone(piece.position) && !in_zone(to) {
+            return None;
+        }
+        Some((
+            *promoted,
+            !Self::is_dead_square(state, piece.kind, piece.color, to),
+        ))
+    }
+    /// The piece `kind` promotes from, or `kind` itself.
+    pub fn demote(&self, kind: PieceKind) -> PieceKind {
+        self.promotions
+            .iter()
+            .find(|(_, promoted)| *promoted == kind)
+            .map_or(kind, |(base, _)| *base)
+    }
+
+    /// Whether `color` may drop a `kind` on the empty square `to`, where
+    /// `enemy_pocket` is what the opponent holds in hand. Leaving the own king
+    /// in check is left to the variant's rules.
+    pub fn allows_drop(
+        &self,
+        state: &BoardState,
+        kind: PieceKind,
+        color: PieceColor,
+        to: GridPosition,
+        enemy_pocket: &[PieceKind],
+    ) -> bool {
+        if Self::is_dead_square(state, kind, color, to) {
+            return false;
+        }
+        if kind != PieceKind::Pawn {
+            return true;
+        }
+        if self.nifu
+            && state.pieces().any(|p| {
+                p.kind == PieceKind::Pawn
+                    && p.color == color
+                    && !p.promoted
+                    && Self::file_of(color, p.position) == Self::file_of(color, to)
+            })
+        {
+            return false;
+        }
+        !(self.no_pawn_drop_mate && Self::drop_mates(state, kind, color, to, enemy_pocket))
+    }
+
+    /// Files run along the direction the color's pawns advance in.
+    fn file_of(color: PieceColor, position: GridPosition) -> u16 {
+        match color.front() {
+            (0, _) => position.x,
+            _ => position.y,
+        }
+    }
+    /// Whether a `kind` of `color` at `position` would have no move at all,
+    /// even on an otherwise empty board.
+    fn is_dead_square(
+        state: &BoardState,
+        kind: PieceKind,
+        color: PieceColor,
+        position: GridPosition,
+    ) -> bool {
+        let mut empty = state.clone();
+        empty.state.clear();
+        empty.en_passant = None;
+        empty.castling = Default::default();
+        let piece = Piece {
+            kind,
+            color,
+            position,
+            promoted: false,
+        };
+        empty.place(piece.clone());
+        piece.pseudo_moveset(&empty).is_empty()
+    }
+    /// Whether dropping `kind` on `to` leaves the opponent checkmated.
+    fn drop_mates(
+        state: &BoardState,
+        kind: PieceKind,
+        color: PieceColor,
+        to: GridPosition,
+        enemy_pocket: &[PieceKind],
+    ) -> bool {
+        let rules = state.variant.rules();
+        let enemy = color.opposite();
+        let mut after = state.clone();
+        after.place(Piece {
+            kind,
+            color,
+            position: to,
+            promoted: false,
+        });
+        if !rules.in_check(&mut after, enemy) {
+            return false;
+        }
+        let can_move = after.pieces().filter(|p| p.color == enemy).any(|p| {
+            p.pseudo_moveset(&after)
+                .into_iter()
+                .any(|mov| p.clone().move_to(&mut after.clone(), mov).is_ok())
+        });
+        if can_move {
+            return false;
+        }
+        let flags =
+            SquareQueryFlags::IN_BOUNDS | SquareQueryFlags::PLAYABLE | SquareQueryFlags::UNBLOCKED;
+        let empty_squares: Vec<GridPosition> = (0..after.num_cells.y)
+            .flat_map(|y| (0..after.num_cells.x).map(move |x| GridPosition { x, y }))
+            .filter(|&p| after.query_square(p, flags) && after.piece_at(p).is_none())
+            .collect();
+        !enemy_pocket.iter().any(|&kind| {
+            empty_squares.iter().any(|&square| {
+                let mut blocked = after.clone();
+                blocked.place(Piece {
+                    kind,
+                    color: enemy,
+                    position: square,
+                    promoted: false,
+                });
+                rules.is_legal(&mut blocked, enemy)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::board::{Board, GridPosition, piece::PieceKind, variant::Variant};
+
+    fn square(name: &str) -> GridPosition {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn nifu() {
+        let board = Board::from_fen(Variant::ShogiHybrid, "4k3/8/8/8/8/8/4P3/4K3[P] w - - 0 1");
+        let drops = board.legal_drops(PieceKind::Pawn);
+        assert!(!drops.iter().any(|&p| p.x == square("e4").x));
+        assert!(drops.contains(&square("d4")));
+    }
+
+    #[test]
+    fn no_pawn_drop_mate() {
+        let mate = "kb6/8/2K5/2B5/8/8/8/8[P] w - - 0 1";
+        let board = Board::from_fen(Variant::ShogiHybrid, mate);
+        assert!(!board.legal_drops(PieceKind::Pawn).contains(&square("b7")));
+
+        // Without the bishop the king escapes to a7, so the check is fine.
+        let check = "kb6/8/2K5/8/8/8/8/8[P] w - - 0 1";
+        let board = Board::from_fen(Variant::ShogiHybrid, check);
+        assert!(board.legal_drops(PieceKind::Pawn).contains(&square("b7")));
+    }
+
+    #[test]
+    fn promotion_zone() {
+        let fen = "4k3/P7/8/1P6/8/8/8/4K3 w - - 0 1";
+        let mut board = Board::from_fen(Variant::ShogiHybrid, fen);
+        board.try_move_piece(square("b5"), square("b6")).unwrap();
+        let pending = board.pending_promotion().unwrap();
+        assert!(pending.optional);
+        assert_eq!(pending.choices, [PieceKind::Queen]);
+
+        // A pawn on the last rank could never move again.
+        let mut board = Board::from_fen(Variant::ShogiHybrid, fen);
+        board.try_move_piece(square("a7"), square("a8")).unwrap();
+        assert!(board.pending_promotion().is_none());
+        let promoted = board.piece_at(square("a8")).unwrap();
+        assert_eq!(promoted.kind, PieceKind::Queen);
+        assert!(promoted.promoted);
+    }
+}
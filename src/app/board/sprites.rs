@@ -1,11 +1,74 @@
-use macroquad::texture::Texture2D;
+use macroquad::{
+    color::Color,
+    math::{Rect, Vec2},
+    texture::{DrawTextureParams, Texture2D, draw_texture_ex},
+};
 use serde::Deserialize;
 
-use super::GridPosition;
+use super::{GridPosition, piece::PieceKind};
 
+#[derive(Clone)]
 pub struct SpritesMap {
     pub atlas: Texture2D,
     pub mappings: PieceMappings,
+    /// Size in pixels of each cell of the atlas.
+    pub tile_size: f32,
+    /// Rows added to the cells of `mappings`, to address one side's half of
+    /// an atlas holding both.
+    pub row_offset: u16,
+}
+impl SpritesMap {
+    /// Size in pixels of each cell of the PNG atlases.
+    pub const TILE_SIZE: f32 = 128.0;
+
+    /// Sprites of a single side, with `atlas` holding only its pieces.
+    pub fn new(atlas: Texture2D, mappings: PieceMappings) -> Self {
+        Self {
+            atlas,
+            mappings,
+            tile_size: Self::TILE_SIZE,
+            row_offset: 0,
+        }
+    }
+    /// White's and Black's sprites from an atlas holding both, Black's cells
+    /// lying [`PieceMappings::black_row`] rows below White's.
+    pub fn shared(atlas: Texture2D, mappings: PieceMappings) -> (Self, Self) {
+        let white = Self::new(atlas, mappings);
+        let black = Self {
+            row_offset: mappings.black_row.unwrap_or_default(),
+            ..white.clone()
+        };
+        (white, black)
+    }
+
+    /// Draws `kind` with its top left corner at `position`, scaled to `size`.
+    pub fn draw_piece(&self, kind: PieceKind, position: Vec2, size: Vec2, modulate: Color) {
+        self.draw_tile(kind.atlas_offset(&self.mappings), position, size, modulate);
+    }
+    /// Draws the duck like [`SpritesMap::draw_piece`] does pieces.
+    pub fn draw_duck(&self, position: Vec2, size: Vec2, modulate: Color) {
+        self.draw_tile(self.mappings.duck, position, size, modulate);
+    }
+    fn draw_tile(&self, offset: GridPosition, position: Vec2, size: Vec2, modulate: Color) {
+        let GridPosition { x: ax, y: ay } = offset;
+        let ay = ay + self.row_offset;
+        draw_texture_ex(
+            &self.atlas,
+            position.x,
+            position.y,
+            modulate,
+            DrawTextureParams {
+                dest_size: Some(size),
+                source: Some(Rect {
+                    x: ax as f32 * self.tile_size,
+                    y: ay as f32 * self.tile_size,
+                    w: self.tile_size,
+                    h: self.tile_size,
+                }),
+                ..Default::default()
+            },
+        );
+    }
 }
 #[derive(Deserialize, Clone, Copy)]
 #[serde(deny_unknown_fields)]
@@ -16,4 +79,12 @@ pub struct PieceMappings {
     pub knight: GridPosition,
     pub king: GridPosition,
     pub queen: GridPosition,
+    pub archbishop: GridPosition,
+    pub chancellor: GridPosition,
+    /// The neutral blocker of Duck chess.
+    pub duck: GridPosition,
+    /// Rows between White's and Black's cells when a single atlas holds both
+    /// sides, White's being the ones mapped.
+    #[serde(default)]
+    pub black_row: Option<u16>,
 }
@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use macroquad::texture::Texture2D;
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{Options, Tree},
+};
+use tracing::warn;
+
+use super::{
+    GridPosition,
+    piece::{PieceColor, PieceKind},
+    sprites::{PieceMappings, SpritesMap},
+};
+
+/// Kinds laid out in the columns of the rasterised atlas, the duck taking
+/// the column after them.
+const KINDS: [PieceKind; 8] = [
+    PieceKind::Pawn,
+    PieceKind::Rook,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+    PieceKind::King,
+    PieceKind::Queen,
+    PieceKind::Archbishop,
+    PieceKind::Chancellor,
+];
+
+/// Piece sprites drawn from SVG files, rasterised into an atlas at the size
+/// the pieces are drawn at so that they stay sharp.
+pub struct SvgPieces {
+    /// White's pieces then Black's, in the order of [`KINDS`].
+    pieces: [Vec<Option<Tree>>; 2],
+    duck: Option<Tree>,
+}
+impl SvgPieces {
+    /// Reads the files of `dir`, named like `wN.svg` and `bN.svg` after the
+    /// color and the FEN letter of each piece, and `duck.svg`. Missing pieces
+    /// are left blank.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let read = |stem: &str| {
+            let path = dir.join(format!("{stem}.svg"));
+            let data = std::fs::read(&path).ok()?;
+            Tree::from_data(&data, &Options::default())
+                .inspect_err(|err| warn!("Skipping {}: {err}", path.display()))
+                .ok()
+        };
+        let side = |prefix: char| -> Vec<Option<Tree>> {
+            KINDS
+                .iter()
+                .map(|kind| read(&format!("{prefix}{}", kind.fen_char(PieceColor::White))))
+                .collect()
+        };
+        let res = Self {
+            pieces: [side('w'), side('b')],
+            duck: read("duck"),
+        };
+        if res.pieces.iter().flatten().all(Option::is_none) {
+            return Err(format!("No piece SVG in {}", dir.display()));
+        }
+        Ok(res)
+    }
+
+    /// Renders every piece into an atlas with `tile_size` pixels per cell,
+    /// returning White's and Black's sprites.
+    pub fn rasterize(&self, tile_size: f32) -> (SpritesMap, SpritesMap) {
+        let px = tile_size.round().max(1.0) as u32;
+        let columns = KINDS.len() as u32 + 1;
+        let mut pixmap = Pixmap::new(columns * px, 2 * px).expect("The atlas isn't empty");
+        let tiles = self.pieces.iter().enumerate().flat_map(|(row, side)| {
+            side.iter()
+                .enumerate()
+                .map(move |(column, tree)| (column, row, tree))
+        });
+        for (column, row, tree) in tiles.chain([(KINDS.len(), 0, &self.duck)]) {
+            let Some(tree) = tree else {
+                continue;
+            };
+            let size = tree.size();
+            let scale = px as f32 / size.width().max(size.height());
+            let transform = Transform::from_scale(scale, scale).post_translate(
+                column as f32 * px as f32 + (px as f32 - size.width() * scale) / 2.0,
+                row as f32 * px as f32 + (px as f32 - size.height() * scale) / 2.0,
+            );
+            resvg::render(tree, transform, &mut pixmap.as_mut());
+        }
+        // The pixmap is premultiplied, textures aren't.
+        let bytes: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        let atlas = Texture2D::from_rgba8(pixmap.width() as u16, pixmap.height() as u16, &bytes);
+        let cell = |kind: PieceKind| GridPosition {
+            x: KINDS.iter().position(|k| *k == kind).unwrap_or_default() as u16,
+            y: 0,
+        };
+        let mappings = PieceMappings {
+            pawn: cell(PieceKind::Pawn),
+            rook: cell(PieceKind::Rook),
+            bishop: cell(PieceKind::Bishop),
+            knight: cell(PieceKind::Knight),
+            king: cell(PieceKind::King),
+            queen: cell(PieceKind::Queen),
+            archbishop: cell(PieceKind::Archbishop),
+            chancellor: cell(PieceKind::Chancellor),
+            duck: GridPosition {
+                x: KINDS.len() as u16,
+                y: 0,
+            },
+            black_row: Some(1),
+        };
+        let (mut white, mut black) = SpritesMap::shared(atlas, mappings);
+        white.tile_size = px as f32;
+        black.tile_size = px as f32;
+        (white, black)
+    }
+}
@@ -0,0 +1,561 @@
+use std::str::FromStr;
+
+use macroquad::math::U16Vec2;
+
+use super::{
+    GridPosition,
+    fen::Position,
+    piece::{Piece, PieceColor, PieceKind},
+    rules::{
+        Antichess, Atomic, Duck, FogOfWar, FourPlayer, KingOfTheHill, Scoring, Standard,
+        ThreeCheck, VariantRules,
+    },
+    shape::BoardShape,
+    shogi::ShogiRules,
+};
+
+/// Built-in game presets.
+/// A variant describes the board dimensions, the starting array and the rules
+/// that differ between chess flavours, such as pawn promotion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// 10x8 board with an Archbishop and a Chancellor between the usual pieces.
+    Capablanca,
+    /// 10x10 board with an Archbishop and a Chancellor and an extra rank
+    /// between the armies.
+    Grand,
+    /// Fischer Random, holding the index of its starting array.
+    /// See [`chess960_array`].
+    Chess960(u16),
+    /// Standard chess where captured pieces join the capturer's pocket and can
+    /// later be dropped back onto the board.
+    Crazyhouse,
+    Atomic,
+    KingOfTheHill,
+    ThreeCheck,
+    /// Losing chess, see [`Antichess`].
+    Antichess,
+    /// 36 white pawns without a king against the standard black army.
+    /// White loses by being wiped out.
+    Horde,
+    /// Dark chess, see [`FogOfWar`].
+    FogOfWar,
+    /// See [`Duck`].
+    Duck,
+    /// Standard armies under Shogi's promotion and drop rules, see
+    /// [`Variant::shogi`].
+    ShogiHybrid,
+    /// Free-for-all between four armies on a 14x14 board with its 3x3 corners
+    /// cut off, see [`FourPlayer`].
+    FourPlayer(Scoring),
+}
+impl Variant {
+    pub fn num_cells(&self) -> U16Vec2 {
+        match self {
+            Variant::Capablanca => U16Vec2 { x: 10, y: 8 },
+            Variant::Grand => U16Vec2 { x: 10, y: 10 },
+            Variant::FourPlayer(_) => U16Vec2 { x: 14, y: 14 },
+            _ => U16Vec2 { x: 8, y: 8 },
+        }
+    }
+    /// Sides taking part, in turn order.
+    pub fn colors(&self) -> &'static [PieceColor] {
+        match self {
+            Variant::FourPlayer(_) => &[
+                PieceColor::White,
+                PieceColor::Blue,
+                PieceColor::Black,
+                PieceColor::Green,
+            ],
+            _ => &[PieceColor::White, PieceColor::Black],
+        }
+    }
+    /// Layout of the board's squares, a cross for four-player chess and a
+    /// plain rectangle otherwise.
+    pub fn shape(&self) -> BoardShape {
+        match self {
+            Variant::FourPlayer(_) => BoardShape::cross(self.num_cells(), 3),
+            _ => BoardShape::rectangle(self.num_cells()),
+        }
+    }
+
+    /// Position a game of this variant starts from.
+    pub fn starting_position(&self) -> Position {
+        let pieces = self
+            .colors()
+            .iter()
+            .flat_map(|&color| self.starting_pieces(color))
+            .collect();
+        let position = Position {
+            shape: self.shape(),
+            pieces,
+            pockets: vec![],
+            turn: PieceColor::White,
+            castling: Default::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove: 1,
+        };
+        if self.has_castling() {
+            position.with_default_castling()
+        } else {
+            position
+        }
+    }
+
+    pub fn has_castling(&self) -> bool {
+        !matches!(
+            self,
+            Variant::Grand | Variant::Antichess | Variant::FourPlayer(_)
+        )
+    }
+    /// Rules deciding move side effects and how the game ends.
+    pub fn rules(&self) -> &'static dyn VariantRules {
+        match self {
+            Variant::Atomic => &Atomic,
+            Variant::KingOfTheHill => &KingOfTheHill,
+            Variant::ThreeCheck => &ThreeCheck,
+            Variant::Antichess => &Antichess,
+            Variant::FogOfWar => &FogOfWar,
+            Variant::Duck => &Duck,
+            Variant::FourPlayer(Scoring::Points) => &FourPlayer::POINTS,
+            Variant::FourPlayer(Scoring::LastKingStanding) => &FourPlayer::LAST_KING_STANDING,
+            _ => &Standard,
+        }
+    }
+    /// Whether each side only sees the squares its pieces can reach.
+    pub fn has_fog(&self) -> bool {
+        matches!(self, Variant::FogOfWar)
+    }
+    /// Whether each move is followed by moving the duck.
+    pub fn has_duck(&self) -> bool {
+        matches!(self, Variant::Duck)
+    }
+    /// Whether captured pieces go to the capturer's pocket to be dropped later.
+    pub fn has_drops(&self) -> bool {
+        matches!(self, Variant::Crazyhouse) || self.shogi().is_some()
+    }
+    /// Shogi-style promotion and drop rules, replacing [`Variant::promotion`]
+    /// and the standard drop restrictions.
+    pub fn shogi(&self) -> Option<ShogiRules> {
+        use PieceKind::*;
+        match self {
+            Variant::ShogiHybrid => Some(ShogiRules {
+                zone: 3,
+                promotions: &[(Pawn, Queen), (Knight, Archbishop), (Rook, Chancellor)],
+                nifu: true,
+                no_pawn_drop_mate: true,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Starting pieces for `color`.
+    /// Black's array is White's mirrored along the horizontal axis, except
+    /// in Horde where the armies differ. In four-player chess every army is
+    /// White's rotated to its own edge.
+    pub fn starting_pieces(&self, color: PieceColor) -> Vec<Piece> {
+        use PieceKind::*;
+        let chess960;
+        let ranks: &[&[Option<PieceKind>]] = match self {
+            Variant::Capablanca => &[
+                &[
+                    Some(Rook),
+                    Some(Knight),
+                    Some(Archbishop),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Bishop),
+                    Some(Chancellor),
+                    Some(Knight),
+                    Some(Rook),
+                ],
+                &[Some(Pawn); 10],
+            ],
+            Variant::Grand => &[
+                &[
+                    Some(Rook),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(Rook),
+                ],
+                &[
+                    None,
+                    Some(Knight),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Chancellor),
+                    Some(Archbishop),
+                    Some(Bishop),
+                    Some(Knight),
+                    None,
+                ],
+                &[Some(Pawn); 10],
+            ],
+            Variant::Horde if color == PieceColor::White => &[
+                &[Some(Pawn); 8],
+                &[Some(Pawn); 8],
+                &[Some(Pawn); 8],
+                &[Some(Pawn); 8],
+                &[
+                    None,
+                    Some(Pawn),
+                    Some(Pawn),
+                    None,
+                    None,
+                    Some(Pawn),
+                    Some(Pawn),
+                    None,
+                ],
+            ],
+            Variant::FourPlayer(_) => &[
+                &[
+                    None,
+                    None,
+                    None,
+                    Some(Rook),
+                    Some(Knight),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Bishop),
+                    Some(Knight),
+                    Some(Rook),
+                    None,
+                    None,
+                    None,
+                ],
+                &[
+                    None,
+                    None,
+                    None,
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    Some(Pawn),
+                    None,
+                    None,
+                    None,
+                ],
+            ],
+            Variant::Chess960(idx) => {
+                chess960 = chess960_array(*idx).map(Some);
+                &[&chess960, &[Some(Pawn); 8]]
+            }
+            _ => &[
+                &[
+                    Some(Rook),
+                    Some(Knight),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Bishop),
+                    Some(Knight),
+                    Some(Rook),
+                ],
+                &[Some(Pawn); 8],
+            ],
+        };
+
+        let U16Vec2 { x: w, y: h } = self.num_cells();
+        let rotated = matches!(self, Variant::FourPlayer(_));
+        let mut res = vec![];
+        for (rank, kinds) in ranks.iter().enumerate() {
+            let rank = rank as u16;
+            for (file, kind) in kinds.iter().enumerate() {
+                let file = file as u16;
+                let (x, y) = match color {
+                    PieceColor::White => (file, rank),
+                    PieceColor::Black if rotated => (w - file - 1, h - rank - 1),
+                    PieceColor::Black => (file, h - rank - 1),
+                    PieceColor::Blue => (rank, file),
+                    PieceColor::Green => (w - rank - 1, h - file - 1),
+                };
+                if let Some(kind) = *kind {
+                    res.push(Piece {
+                        kind,
+                        color,
+                        position: GridPosition { x, y },
+                        promoted: false,
+                    });
+                }
+            }
+        }
+        res
+    }
+
+    /// Ranks, counted from the owner's side starting at 0, from which a pawn
+    /// may advance two squares.
+    pub fn double_step_ranks(&self) -> &'static [u16] {
+        match self {
+            Variant::Grand => &[2],
+            // Horde pawns also start on the first rank.
+            Variant::Horde => &[0, 1],
+            _ => &[1],
+        }
+    }
+
+    pub fn promotion(&self) -> PromotionRule {
+        use PieceKind::*;
+        match self {
+            Variant::Capablanca => PromotionRule {
+                zone: 1,
+                choices: &[Queen, Chancellor, Archbishop, Rook, Bishop, Knight],
+                from_captured: false,
+                mandatory: false,
+            },
+            Variant::Grand => PromotionRule {
+                zone: 3,
+                choices: &[Queen, Chancellor, Archbishop, Rook, Bishop, Knight],
+                from_captured: true,
+                mandatory: false,
+            },
+            Variant::Antichess => PromotionRule {
+                zone: 1,
+                choices: &[Queen, Rook, Bishop, Knight, King],
+                from_captured: false,
+                mandatory: false,
+            },
+            // Pawns promote as soon as they reach the middle of the board.
+            Variant::FourPlayer(_) => PromotionRule {
+                zone: self.num_cells().y / 2,
+                choices: &[Queen],
+                from_captured: false,
+                mandatory: true,
+            },
+            _ => PromotionRule {
+                zone: 1,
+                choices: &[Queen, Rook, Bishop, Knight],
+                from_captured: false,
+                mandatory: false,
+            },
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    /// Parses a variant name. Chess960 takes an optional array index or seed as
+    /// `chess960:518` or `chess960:seed=42`, and picks a random array otherwise.
+    /// Four-player chess is scored by points unless given as `fourplayer:lks`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        let (name, arg) = s.split_once(':').unwrap_or((&s, ""));
+        match (name, arg) {
+            ("standard", "") => Ok(Variant::Standard),
+            ("capablanca", "") => Ok(Variant::Capablanca),
+            ("grand", "") => Ok(Variant::Grand),
+            ("crazyhouse", "") => Ok(Variant::Crazyhouse),
+            ("atomic", "") => Ok(Variant::Atomic),
+            ("kingofthehill" | "koth", "") => Ok(Variant::KingOfTheHill),
+            ("threecheck" | "3check", "") => Ok(Variant::ThreeCheck),
+            ("antichess" | "losing", "") => Ok(Variant::Antichess),
+            ("horde", "") => Ok(Variant::Horde),
+            ("fogofwar" | "dark", "") => Ok(Variant::FogOfWar),
+            ("duck", "") => Ok(Variant::Duck),
+            ("shogihybrid", "") => Ok(Variant::ShogiHybrid),
+            ("fourplayer" | "4player", "" | "points") => Ok(Variant::FourPlayer(Scoring::Points)),
+            ("fourplayer" | "4player", "lks") => Ok(Variant::FourPlayer(Scoring::LastKingStanding)),
+            ("chess960", "") => {
+                let seed = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default();
+                Ok(Variant::Chess960(chess960_random_index(seed)))
+            }
+            ("chess960", arg) => match arg.strip_prefix("seed=") {
+                Some(seed) => seed
+                    .parse()
+                    .map(|seed| Variant::Chess960(chess960_random_index(seed)))
+                    .map_err(|_| format!("Invalid Chess960 seed '{seed}'")),
+                None => arg
+                    .parse()
+                    .ok()
+                    .filter(|&idx| idx < CHESS960_POSITIONS)
+                    .map(Variant::Chess960)
+                    .ok_or_else(|| format!("Invalid Chess960 index '{arg}'")),
+            },
+            _ => Err(format!("Unknown variant '{s}'")),
+        }
+    }
+}
+
+pub const CHESS960_POSITIONS: u16 = 960;
+
+/// Back rank of the Chess960 starting array with index `idx`, as numbered by
+/// Scharnagl, so that 518 is the standard array.
+pub fn chess960_array(idx: u16) -> [PieceKind; 8] {
+    use PieceKind::*;
+    const KNIGHTS: [(usize, usize); 10] = [
+        (0, 1),
+        (0, 2),
+        (0, 3),
+        (0, 4),
+        (1, 2),
+        (1, 3),
+        (1, 4),
+        (2, 3),
+        (2, 4),
+        (3, 4),
+    ];
+    let mut n = (idx % CHESS960_POSITIONS) as usize;
+    let mut res: [Option<PieceKind>; 8] = [None; 8];
+
+    res[n % 4 * 2 + 1] = Some(Bishop);
+    n /= 4;
+    res[n % 4 * 2] = Some(Bishop);
+    n /= 4;
+    let place = |res: &mut [Option<PieceKind>; 8], nth: usize, kind| {
+        let file = (0..8).filter(|&f| res[f].is_none()).nth(nth).unwrap();
+        res[file] = Some(kind);
+    };
+    place(&mut res, n % 6, Queen);
+    n /= 6;
+    let (k1, k2) = KNIGHTS[n];
+    // Place the second knight first so the first one's index isn't shifted.
+    place(&mut res, k2, Knight);
+    place(&mut res, k1, Knight);
+    for kind in [Rook, King, Rook] {
+        place(&mut res, 0, kind);
+    }
+    res.map(|k| k.unwrap())
+}
+
+/// Picks a Chess960 array index from `seed`, with a splitmix64 step so that
+/// the global generator isn't reseeded.
+pub fn chess960_random_index(seed: u64) -> u16 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z % CHESS960_POSITIONS as u64) as u16
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PromotionRule {
+    /// Number of ranks at the far side of the board in which a pawn may promote.
+    /// Promotion is only compulsory on the last rank, unless `mandatory`.
+    pub zone: u16,
+    pub choices: &'static [PieceKind],
+    /// Pawns may only promote to a piece their side has already lost.
+    pub from_captured: bool,
+    /// Pawns have to promote as soon as they enter the zone.
+    pub mandatory: bool,
+}
+impl PromotionRule {
+    /// Pieces a pawn may currently promote to, given the pieces its side has lost.
+    pub fn available(&self, lost: &[PieceKind]) -> Vec<PieceKind> {
+        self.choices
+            .iter()
+            .copied()
+            .filter(|k| !self.from_captured || lost.contains(k))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::{Board, fen::CastlingNotation};
+
+    fn back_rank(idx: u16) -> String {
+        chess960_array(idx)
+            .iter()
+            .map(|k| k.fen_char(PieceColor::White))
+            .collect()
+    }
+
+    #[test]
+    fn chess960_numbering() {
+        assert_eq!(back_rank(518), "RNBQKBNR");
+        assert_eq!(back_rank(0), "BBQNNRKR");
+        assert_eq!(back_rank(959), "RKRNNQBB");
+    }
+
+    #[test]
+    fn chess960_random_index_is_stable() {
+        for seed in [0, 1, 42, u64::MAX] {
+            let idx = chess960_random_index(seed);
+            assert!(idx < CHESS960_POSITIONS);
+            assert_eq!(idx, chess960_random_index(seed));
+        }
+    }
+
+    #[test]
+    fn chess960_castling_field() {
+        let position = Variant::Chess960(518).starting_position();
+        let field = |notation| {
+            position
+                .to_fen(notation)
+                .split(' ')
+                .nth(2)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(field(CastlingNotation::XFen), "KQkq");
+        assert_eq!(field(CastlingNotation::Shredder), "HAha");
+    }
+
+    #[test]
+    fn chess960_fen_round_trip() {
+        for idx in [0, 518, 959] {
+            let position = Variant::Chess960(idx).starting_position();
+            for notation in [CastlingNotation::XFen, CastlingNotation::Shredder] {
+                let fen = position.to_fen(notation);
+                let parsed: Position = fen.parse().unwrap();
+                assert_eq!(
+                    parsed.to_fen(CastlingNotation::XFen),
+                    position.to_fen(CastlingNotation::XFen)
+                );
+                assert_eq!(
+                    parsed.to_fen(CastlingNotation::Shredder),
+                    position.to_fen(CastlingNotation::Shredder)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn horde_army() {
+        let position = Variant::Horde.starting_position();
+        let white: Vec<_> = position
+            .pieces
+            .iter()
+            .filter(|p| p.color == PieceColor::White)
+            .collect();
+        assert_eq!(white.len(), 36);
+        assert!(white.iter().all(|p| p.kind == PieceKind::Pawn));
+    }
+
+    #[test]
+    fn horde_pawns_double_step_from_the_first_rank() {
+        let fen = "4k3/8/8/8/8/8/1P6/P7 w - - 0 1";
+        let square = |name: &str| name.parse().unwrap();
+        let board = Board::from_fen(Variant::Horde, fen);
+        assert_eq!(
+            board.legal_moves(square("a1")),
+            [square("a2"), square("a3")]
+        );
+        assert_eq!(
+            board.legal_moves(square("b2")),
+            [square("b3"), square("b4")]
+        );
+        let board = Board::from_fen(Variant::Standard, fen);
+        assert_eq!(board.legal_moves(square("a1")), [square("a2")]);
+    }
+}
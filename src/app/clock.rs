@@ -0,0 +1,315 @@
+use std::str::FromStr;
+
+use macroquad::{
+    color::{Color, DARKGRAY, LIGHTGRAY, RED, WHITE},
+    math::Rect,
+    shapes::draw_rectangle,
+    text::draw_text,
+};
+
+use super::board::piece::PieceColor;
+
+/// Time added around each move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bonus {
+    None,
+    /// Seconds added after every move.
+    Fischer(f32),
+    /// Seconds of each move given back after it, up to the time it took.
+    Bronstein(f32),
+    /// Seconds each move may take before the clock starts running.
+    Delay(f32),
+}
+
+/// One period of a time control.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stage {
+    /// Moves to be played within the stage, `None` for the rest of the game.
+    pub moves: Option<u32>,
+    /// Seconds added when the stage starts.
+    pub time: f32,
+    pub bonus: Bonus,
+}
+
+/// Stages played in order. The last one repeats if it has a move count.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeControl {
+    stages: Vec<Stage>,
+}
+impl TimeControl {
+    fn stage(&self, idx: usize) -> &Stage {
+        &self.stages[idx.min(self.stages.len() - 1)]
+    }
+}
+impl FromStr for TimeControl {
+    type Err = String;
+
+    /// Parses stages separated by `:`, each written as `[moves/]minutes`
+    /// followed by an optional bonus in seconds: `+` for an increment, `b` for
+    /// a Bronstein delay and `d` for a simple delay. For example `5+3` is blitz
+    /// and `40/90+30:30+30` the FIDE classical control.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid time control '{s}'");
+        let stages = s
+            .split(':')
+            .map(|stage| {
+                let (moves, rest) = match stage.split_once('/') {
+                    Some((moves, rest)) => (Some(moves.parse().map_err(|_| invalid())?), rest),
+                    None => (None, stage),
+                };
+                let (minutes, bonus) = match rest.find(['+', 'b', 'd']) {
+                    Some(idx) => {
+                        let seconds: f32 = rest[idx + 1..].parse().map_err(|_| invalid())?;
+                        let bonus = match &rest[idx..=idx] {
+                            "+" => Bonus::Fischer(seconds),
+                            "b" => Bonus::Bronstein(seconds),
+                            _ => Bonus::Delay(seconds),
+                        };
+                        (&rest[..idx], bonus)
+                    }
+                    None => (rest, Bonus::None),
+                };
+                let minutes: f32 = minutes.parse().map_err(|_| invalid())?;
+                Ok(Stage {
+                    moves,
+                    time: minutes * 60.0,
+                    bonus,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { stages })
+    }
+}
+
+struct SideClock {
+    color: PieceColor,
+    /// Seconds left.
+    remaining: f32,
+    stage: usize,
+    /// Moves played in the current stage.
+    moves: u32,
+    /// Seconds spent on the move being played.
+    spent: f32,
+}
+
+/// A clock for each side, only the one of the side to play running.
+pub struct Clock {
+    control: TimeControl,
+    sides: Vec<SideClock>,
+}
+impl Clock {
+    pub fn new(control: TimeControl, colors: &[PieceColor]) -> Self {
+        let time = control.stage(0).time;
+        Self {
+            sides: colors
+                .iter()
+                .map(|&color| SideClock {
+                    color,
+                    remaining: time,
+                    stage: 0,
+                    moves: 0,
+                    spent: 0.0,
+                })
+                .collect(),
+            control,
+        }
+    }
+    fn side(&self, color: PieceColor) -> &SideClock {
+        self.sides
+            .iter()
+            .find(|s| s.color == color)
+            .expect("Every color of the variant has a clock")
+    }
+    fn side_mut(&mut self, color: PieceColor) -> &mut SideClock {
+        self.sides
+            .iter_mut()
+            .find(|s| s.color == color)
+            .expect("Every color of the variant has a clock")
+    }
+
+    /// Seconds `color` has left, not counting any pending delay.
+    pub fn remaining(&self, color: PieceColor) -> f32 {
+        self.side(color).remaining
+    }
+    /// Moves `color` has to make before the next time control, `None` once
+    /// the stage lasts the rest of the game.
+    pub fn moves_to_control(&self, color: PieceColor) -> Option<u32> {
+        let side = self.side(color);
+        self.control
+            .stage(side.stage)
+            .moves
+            .map(|moves| moves - side.moves)
+    }
+
+    /// Runs `color`'s clock for `dt` seconds, returning whether its flag fell.
+    pub fn tick(&mut self, color: PieceColor, dt: f32) -> bool {
+        let stage = *self.control.stage(self.side(color).stage);
+        let side = self.side_mut(color);
+        let delay = match stage.bonus {
+            Bonus::Delay(delay) => delay,
+            _ => 0.0,
+        };
+        let charged = (side.spent + dt - delay.max(side.spent)).max(0.0);
+        side.spent += dt;
+        side.remaining -= charged;
+        side.remaining <= 0.0
+    }
+    /// Stops `color`'s clock after it completed a move, adding its bonus and
+    /// the time of the next stage when a control is reached.
+    pub fn press(&mut self, color: PieceColor) {
+        let stage = *self.control.stage(self.side(color).stage);
+        let side = self.side_mut(color);
+        match stage.bonus {
+            Bonus::Fischer(increment) => side.remaining += increment,
+            Bonus::Bronstein(delay) => side.remaining += delay.min(side.spent),
+            Bonus::None | Bonus::Delay(_) => (),
+        }
+        side.spent = 0.0;
+        side.moves += 1;
+        if stage.moves.is_some_and(|moves| side.moves >= moves) {
+            side.stage += 1;
+            side.moves = 0;
+            let next = side.stage;
+            let time = self.control.stage(next).time;
+            self.side_mut(color).remaining += time;
+        }
+    }
+
+    /// Draws a clock per side stacked from `rect`'s top, with `running`'s
+    /// highlighted.
+    pub fn draw(&self, rect: Rect, running: Option<PieceColor>) {
+        const HEIGHT: f32 = 72.0;
+        for (idx, side) in self.sides.iter().enumerate() {
+            let y = rect.y + idx as f32 * (HEIGHT + 8.0);
+            let background = if running == Some(side.color) {
+                Color {
+                    r: 0.3,
+                    g: 0.45,
+                    b: 0.7,
+                    a: 1.0,
+                }
+            } else {
+                DARKGRAY
+            };
+            draw_rectangle(rect.x, y, rect.w, HEIGHT, background);
+            let remaining = self.remaining(side.color);
+            let color = if remaining <= 0.0 { RED } else { WHITE };
+            draw_text(&format_time(remaining), rect.x + 8.0, y + 44.0, 48.0, color);
+            let mut label = format!("{:?}", side.color);
+            if let Some(moves) = self.moves_to_control(side.color) {
+                label.push_str(&format!(" ({moves} to go)"));
+            }
+            draw_text(&label, rect.x + 8.0, y + HEIGHT - 6.0, 20.0, LIGHTGRAY);
+        }
+    }
+}
+
+/// `h:mm:ss`, `m:ss`, or `s.t` in the last ten seconds.
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0);
+    if seconds < 10.0 {
+        return format!("{seconds:.1}");
+    }
+    let total = seconds as u32;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: [PieceColor; 2] = [PieceColor::White, PieceColor::Black];
+
+    fn clock(control: &str) -> Clock {
+        Clock::new(control.parse().unwrap(), &COLORS)
+    }
+
+    #[test]
+    fn parse_blitz() {
+        let control: TimeControl = "5+3".parse().unwrap();
+        assert_eq!(
+            control.stages,
+            [Stage {
+                moves: None,
+                time: 300.0,
+                bonus: Bonus::Fischer(3.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_classical() {
+        let control: TimeControl = "40/90+30:30+30".parse().unwrap();
+        assert_eq!(
+            control.stages,
+            [
+                Stage {
+                    moves: Some(40),
+                    time: 5400.0,
+                    bonus: Bonus::Fischer(30.0),
+                },
+                Stage {
+                    moves: None,
+                    time: 1800.0,
+                    bonus: Bonus::Fischer(30.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("".parse::<TimeControl>().is_err());
+        assert!("5+".parse::<TimeControl>().is_err());
+        assert!("x/5".parse::<TimeControl>().is_err());
+    }
+
+    #[test]
+    fn bronstein_gives_back_at_most_the_time_spent() {
+        let mut clock = clock("1b5");
+        clock.tick(PieceColor::White, 2.0);
+        clock.press(PieceColor::White);
+        assert_eq!(clock.remaining(PieceColor::White), 60.0);
+        clock.tick(PieceColor::White, 8.0);
+        clock.press(PieceColor::White);
+        assert_eq!(clock.remaining(PieceColor::White), 57.0);
+    }
+
+    #[test]
+    fn delay_runs_before_the_clock() {
+        let mut clock = clock("1d5");
+        assert!(!clock.tick(PieceColor::White, 3.0));
+        assert!(!clock.tick(PieceColor::White, 2.0));
+        assert_eq!(clock.remaining(PieceColor::White), 60.0);
+        clock.tick(PieceColor::White, 1.0);
+        assert_eq!(clock.remaining(PieceColor::White), 59.0);
+        clock.press(PieceColor::White);
+        // The delay starts over with the next move, crossed within one tick.
+        clock.tick(PieceColor::White, 7.0);
+        assert_eq!(clock.remaining(PieceColor::White), 57.0);
+    }
+
+    #[test]
+    fn flag_falls_at_zero() {
+        let mut clock = clock("1");
+        assert!(!clock.tick(PieceColor::White, 59.0));
+        assert!(clock.tick(PieceColor::White, 1.0));
+        assert_eq!(clock.remaining(PieceColor::Black), 60.0);
+    }
+
+    #[test]
+    fn next_stage_adds_its_time() {
+        let mut clock = clock("2/1:1");
+        assert_eq!(clock.moves_to_control(PieceColor::White), Some(2));
+        clock.press(PieceColor::White);
+        assert_eq!(clock.moves_to_control(PieceColor::White), Some(1));
+        clock.press(PieceColor::White);
+        assert_eq!(clock.moves_to_control(PieceColor::White), None);
+        assert_eq!(clock.remaining(PieceColor::White), 120.0);
+    }
+}
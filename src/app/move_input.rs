@@ -0,0 +1,302 @@
+use macroquad::{
+    color::{DARKGRAY, LIGHTGRAY, WHITE},
+    input::{KeyCode, clear_input_queue, get_char_pressed, is_key_pressed},
+    math::Rect,
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use super::board::{
+    Board, GridPosition,
+    piece::{PieceColor, PieceKind},
+    san::{self, square_name},
+};
+
+/// What playing a typed move does on the board.
+#[derive(Clone, Copy, Debug)]
+pub enum TypedAction {
+    Move {
+        from: GridPosition,
+        to: GridPosition,
+    },
+    Drop {
+        kind: PieceKind,
+        to: GridPosition,
+    },
+    /// Second half of a Duck chess turn, typed as `@e4`.
+    Duck(GridPosition),
+}
+
+/// A legal move, with the ways it can be typed.
+#[derive(Clone, Debug)]
+pub struct TypedMove {
+    pub san: String,
+    /// Origin and destination squares, such as `e2e4`.
+    pub coordinates: String,
+    pub action: TypedAction,
+}
+impl TypedMove {
+    fn matches(&self, text: &str) -> bool {
+        self.san == text || self.coordinates == text
+    }
+    fn starts_with(&self, text: &str) -> bool {
+        self.san.starts_with(text) || self.coordinates.starts_with(text)
+    }
+}
+
+/// Every move `color` can type on `board`, nothing when it isn't its turn.
+pub fn legal_moves(board: &Board, color: PieceColor) -> Vec<TypedMove> {
+    if board.turn() != color || board.result().is_some() || board.pending_promotion().is_some() {
+        return vec![];
+    }
+    if board.duck_pending() {
+        return board
+            .legal_duck_squares()
+            .into_iter()
+            .map(|to| TypedMove {
+                san: format!("@{}", square_name(to)),
+                coordinates: format!("@{}", square_name(to)),
+                action: TypedAction::Duck(to),
+            })
+            .collect();
+    }
+    let position = board.position();
+    let mut res = vec![];
+    for piece in position.pieces.iter().filter(|p| p.color == color) {
+        for to in board.legal_moves(piece.position) {
+            res.push(TypedMove {
+                san: san::move_san(board, piece, to),
+                coordinates: square_name(piece.position) + &square_name(to),
+                action: TypedAction::Move {
+                    from: piece.position,
+                    to,
+                },
+            });
+        }
+    }
+    let mut pocket: Vec<PieceKind> = vec![];
+    for &(kind, c) in &position.pockets {
+        if c == color && !pocket.contains(&kind) {
+            pocket.push(kind);
+        }
+    }
+    for kind in pocket {
+        for to in board.legal_drops(kind) {
+            let san = san::drop_san(kind, to);
+            res.push(TypedMove {
+                coordinates: san.clone(),
+                san,
+                action: TypedAction::Drop { kind, to },
+            });
+        }
+    }
+    res
+}
+
+/// Text box taking moves in SAN or as coordinates, suggesting the legal
+/// moves starting with what was typed.
+pub struct MoveInput {
+    rect: Rect,
+    text: String,
+    open: bool,
+    /// Moves that can be typed in the current position.
+    moves: Vec<TypedMove>,
+}
+impl MoveInput {
+    const FONT_SIZE: f32 = 36.0;
+    const MAX_SUGGESTIONS: usize = 8;
+
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            text: String::new(),
+            open: false,
+            moves: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    /// Opens the box, empty, for the moves given.
+    pub fn open(&mut self, moves: Vec<TypedMove>) {
+        // Keys typed before, such as the one opening the box, aren't moves.
+        clear_input_queue();
+        self.open = true;
+        self.text.clear();
+        self.moves = moves;
+    }
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// What was typed, without the check, capture and annotation marks that
+    /// may be left out.
+    fn typed(&self) -> String {
+        self.text
+            .chars()
+            .filter(|c| !matches!(c, '+' | '#' | '!' | '?'))
+            .collect()
+    }
+    fn suggestions(&self) -> Vec<&TypedMove> {
+        let typed = self.typed();
+        self.moves
+            .iter()
+            .filter(|m| m.starts_with(&typed))
+            .collect()
+    }
+    /// The move typed, followed by the piece to promote to when one was
+    /// given, as in `e8=Q` or `e7e8q`. Only whole moves are taken, prefixes
+    /// are left to completion.
+    fn parse(&self) -> Option<(TypedAction, Option<PieceKind>)> {
+        let typed = self.typed();
+        if let Some(found) = self.moves.iter().find(|m| m.matches(&typed)) {
+            return Some((found.action, None));
+        }
+        let mut chars = typed.chars();
+        let promotion = chars
+            .next_back()
+            .and_then(PieceKind::from_fen_char)
+            .map(|(kind, _)| kind)
+            .filter(|kind| !matches!(kind, PieceKind::Pawn | PieceKind::King));
+        if let Some(kind) = promotion {
+            let base = chars.as_str().trim_end_matches('=');
+            if let Some(found) = self.moves.iter().find(|m| m.matches(base)) {
+                return Some((found.action, Some(kind)));
+            }
+        }
+        None
+    }
+
+    /// Takes the keys typed this frame, returning the move entered with Enter.
+    /// Tab completes the first suggestion. Keys typed while the box is closed
+    /// are dropped.
+    pub fn update(&mut self) -> Option<(TypedAction, Option<PieceKind>)> {
+        // The queue is only emptied by taking from it, last typed first.
+        let mut typed = vec![];
+        while let Some(c) = get_char_pressed() {
+            typed.push(c);
+        }
+        if !self.open {
+            return None;
+        }
+        typed.reverse();
+        self.text
+            .extend(typed.into_iter().filter(|c| c.is_ascii_graphic()));
+        if is_key_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+        if is_key_pressed(KeyCode::Tab)
+            && let Some(first) = self.suggestions().first()
+        {
+            self.text = first.san.clone();
+        }
+        if !is_key_pressed(KeyCode::Enter) && !is_key_pressed(KeyCode::KpEnter) {
+            return None;
+        }
+        let entered = self.parse();
+        if entered.is_some() {
+            self.text.clear();
+        }
+        entered
+    }
+
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        let Rect { x, y, w, h } = self.rect;
+        draw_rectangle(x, y, w, h, DARKGRAY);
+        draw_rectangle_lines(x, y, w, h, 2.0, LIGHTGRAY);
+        draw_text(
+            &format!("{}_", self.text),
+            x + 12.0,
+            y + (h + Self::FONT_SIZE) / 2.0 - 6.0,
+            Self::FONT_SIZE,
+            WHITE,
+        );
+        let suggestions: Vec<&str> = self
+            .suggestions()
+            .into_iter()
+            .take(Self::MAX_SUGGESTIONS)
+            .map(|m| m.san.as_str())
+            .collect();
+        draw_text(
+            &suggestions.join("  "),
+            x,
+            y + h + Self::FONT_SIZE,
+            Self::FONT_SIZE * 0.8,
+            LIGHTGRAY,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::variant::Variant;
+
+    fn input(fen: &str, text: &str) -> MoveInput {
+        let board = Board::from_fen(Variant::Standard, fen);
+        MoveInput {
+            rect: Rect::default(),
+            text: text.to_string(),
+            open: true,
+            moves: legal_moves(&board, board.turn()),
+        }
+    }
+    fn squares(action: TypedAction) -> (String, String) {
+        match action {
+            TypedAction::Move { from, to } => (square_name(from), square_name(to)),
+            _ => panic!("Not a move: {action:?}"),
+        }
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const PROMOTION: &str = "k7/4P3/8/8/8/8/8/4K3 w - - 0 1";
+
+    #[test]
+    fn exact_san() {
+        let (action, promotion) = input(START, "Nf3").parse().unwrap();
+        assert_eq!(squares(action), ("g1".into(), "f3".into()));
+        assert_eq!(promotion, None);
+        let (action, _) = input(START, "e4+!").parse().unwrap();
+        assert_eq!(squares(action), ("e2".into(), "e4".into()));
+    }
+
+    #[test]
+    fn coordinates() {
+        let (action, promotion) = input(START, "g1f3").parse().unwrap();
+        assert_eq!(squares(action), ("g1".into(), "f3".into()));
+        assert_eq!(promotion, None);
+    }
+
+    #[test]
+    fn promotions() {
+        for (text, kind) in [
+            ("e8=Q", Some(PieceKind::Queen)),
+            ("e8N", Some(PieceKind::Knight)),
+            ("e7e8q", Some(PieceKind::Queen)),
+            ("e7e8r", Some(PieceKind::Rook)),
+            ("e8", None),
+        ] {
+            let (action, promotion) = input(PROMOTION, text).parse().unwrap();
+            assert_eq!(squares(action), ("e7".into(), "e8".into()), "{text}");
+            assert_eq!(promotion, kind, "{text}");
+        }
+        assert!(input(PROMOTION, "e8=K").parse().is_none());
+    }
+
+    #[test]
+    fn prefixes_only_complete() {
+        // Nf3 is the only move starting with Nf, but it wasn't typed.
+        let typed = input(START, "Nf");
+        assert!(typed.parse().is_none());
+        assert_eq!(typed.suggestions().len(), 1);
+        let typed = input(START, "N");
+        assert!(typed.parse().is_none());
+        assert_eq!(typed.suggestions().len(), 4);
+        assert!(input(START, "e5").parse().is_none());
+    }
+}
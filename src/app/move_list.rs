@@ -0,0 +1,142 @@
+use macroquad::{
+    color::{Color, LIGHTGRAY, WHITE},
+    math::{Rect, Vec2},
+    shapes::draw_rectangle,
+    text::draw_text,
+};
+
+use super::board::{piece::PieceColor, san::PlayedMove};
+
+/// Side panel listing the moves played, one row per full move with a column
+/// for each side.
+pub struct MoveList {
+    rect: Rect,
+    /// First row shown.
+    scroll: usize,
+}
+impl MoveList {
+    const ROW_HEIGHT: f32 = 36.0;
+    const NUMBER_WIDTH: f32 = 80.0;
+    const FONT_SIZE: f32 = 32.0;
+
+    pub fn new(rect: Rect) -> Self {
+        Self { rect, scroll: 0 }
+    }
+
+    /// Row and column each move is listed in.
+    fn cells(moves: &[PlayedMove], colors: &[PieceColor]) -> Vec<(usize, usize)> {
+        let first = moves.first().map_or(0, |m| m.fullmove);
+        moves
+            .iter()
+            .map(|m| {
+                let column = colors.iter().position(|c| *c == m.color).unwrap_or(0);
+                ((m.fullmove - first) as usize, column)
+            })
+            .collect()
+    }
+    fn visible_rows(&self) -> usize {
+        (self.rect.h / Self::ROW_HEIGHT) as usize
+    }
+    fn column_width(&self, colors: &[PieceColor]) -> f32 {
+        (self.rect.w - Self::NUMBER_WIDTH) / colors.len() as f32
+    }
+    fn cell_rect(&self, row: usize, column: usize, colors: &[PieceColor]) -> Rect {
+        let width = self.column_width(colors);
+        Rect {
+            x: self.rect.x + Self::NUMBER_WIDTH + column as f32 * width,
+            y: self.rect.y + (row - self.scroll) as f32 * Self::ROW_HEIGHT,
+            w: width,
+            h: Self::ROW_HEIGHT,
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.rect.contains(pos)
+    }
+    /// Scrolls by `rows`, up when negative, staying within the list.
+    pub fn scroll_by(&mut self, rows: isize, moves: &[PlayedMove], colors: &[PieceColor]) {
+        let count = Self::cells(moves, colors)
+            .last()
+            .map_or(0, |(row, _)| row + 1);
+        let max = count.saturating_sub(self.visible_rows());
+        self.scroll = self.scroll.saturating_add_signed(rows).min(max);
+    }
+    /// Scrolls just enough for the move at `idx` to be shown.
+    pub fn reveal(&mut self, idx: usize, moves: &[PlayedMove], colors: &[PieceColor]) {
+        let Some(&(row, _)) = Self::cells(moves, colors).get(idx) else {
+            return;
+        };
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + self.visible_rows() {
+            self.scroll = row + 1 - self.visible_rows();
+        }
+    }
+    /// Index of the move listed at `pos`.
+    pub fn move_at(&self, pos: Vec2, moves: &[PlayedMove], colors: &[PieceColor]) -> Option<usize> {
+        Self::cells(moves, colors)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (row, _))| (self.scroll..self.scroll + self.visible_rows()).contains(row))
+            .find(|(_, (row, column))| self.cell_rect(*row, *column, colors).contains(pos))
+            .map(|(idx, _)| idx)
+    }
+
+    /// Draws the list, with the move at `current` highlighted.
+    pub fn draw(&self, moves: &[PlayedMove], colors: &[PieceColor], current: Option<usize>) {
+        draw_rectangle(
+            self.rect.x,
+            self.rect.y,
+            self.rect.w,
+            self.rect.h,
+            Color {
+                r: 0.15,
+                g: 0.15,
+                b: 0.15,
+                a: 1.0,
+            },
+        );
+        let visible = self.scroll..self.scroll + self.visible_rows();
+        let cells = Self::cells(moves, colors);
+        let baseline = (Self::ROW_HEIGHT + Self::FONT_SIZE) / 2.0 - 4.0;
+        let mut numbered = None;
+        for (idx, (played, &(row, column))) in moves.iter().zip(cells.iter()).enumerate() {
+            if !visible.contains(&row) {
+                continue;
+            }
+            let rect = self.cell_rect(row, column, colors);
+            if numbered != Some(row) {
+                numbered = Some(row);
+                draw_text(
+                    &format!("{}.", played.fullmove),
+                    self.rect.x + 8.0,
+                    rect.y + baseline,
+                    Self::FONT_SIZE,
+                    LIGHTGRAY,
+                );
+            }
+            if current == Some(idx) {
+                draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color {
+                        r: 0.3,
+                        g: 0.45,
+                        b: 0.7,
+                        a: 1.0,
+                    },
+                );
+            }
+            draw_text(
+                &played.san,
+                rect.x + 8.0,
+                rect.y + baseline,
+                Self::FONT_SIZE,
+                WHITE,
+            );
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use super::board::{
+    Board,
+    fen::CastlingNotation,
+    piece::PieceColor,
+    rules::{DrawReason, GameResult},
+    variant::Variant,
+};
+
+/// Value of the `Variant` tag, as lichess and other PGN readers name the
+/// variants they know.
+fn variant_tag(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => "Standard",
+        Variant::Capablanca => "Capablanca",
+        Variant::Grand => "Grand",
+        Variant::Chess960(_) => "Chess960",
+        Variant::Crazyhouse => "Crazyhouse",
+        Variant::Atomic => "Atomic",
+        Variant::KingOfTheHill => "King of the Hill",
+        Variant::ThreeCheck => "Three-check",
+        Variant::Antichess => "Antichess",
+        Variant::Horde => "Horde",
+        Variant::FogOfWar => "Dark chess",
+        Variant::Duck => "Duck chess",
+        Variant::ShogiHybrid => "Shogi hybrid",
+        Variant::FourPlayer(_) => "Four-player",
+    }
+}
+
+/// Writes the game as PGN. `history` holds the board after each move,
+/// starting with the initial position, and the marks drawn on each position
+/// go in the comment after the move reaching it as `[%csl]` and `[%cal]`
+/// commands.
+pub fn write(history: &[Board], live: &Board) -> String {
+    let variant = live.variant();
+    let result = match live.result() {
+        Some(GameResult::Win {
+            winner: PieceColor::White,
+            ..
+        }) => "1-0",
+        Some(GameResult::Win { .. }) => "0-1",
+        Some(GameResult::Draw(DrawReason::TiedPoints)) | None => "*",
+        Some(GameResult::Draw(_)) => "1/2-1/2",
+    };
+    let mut res = format!(
+        "[Variant \"{}\"]\n[Result \"{result}\"]\n",
+        variant_tag(variant)
+    );
+    // The tag doesn't tell which Chess960 array was played, the FEN has to.
+    let fen = history[0].position().to_fen(CastlingNotation::XFen);
+    if matches!(variant, Variant::Chess960(_))
+        || fen != variant.starting_position().to_fen(CastlingNotation::XFen)
+    {
+        res.push_str(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n"));
+    }
+    res.push('\n');
+
+    let mut tokens = vec![];
+    let comment = |board: &Board| {
+        let annotations = board.annotations();
+        (!annotations.is_empty()).then(|| format!("{{ {} }}", annotations.to_pgn_commands()))
+    };
+    tokens.extend(comment(&history[0]));
+    for (idx, played) in live.moves().iter().enumerate() {
+        if played.color == PieceColor::White {
+            tokens.push(format!("{}.", played.fullmove));
+        } else if idx == 0 || tokens.last().is_some_and(|t| t.starts_with('{')) {
+            tokens.push(format!("{}...", played.fullmove));
+        }
+        tokens.push(played.san.clone());
+        if let Some(board) = history.get(idx + 1) {
+            tokens.extend(comment(board));
+        }
+    }
+    tokens.push(result.to_string());
+    res.push_str(&tokens.join(" "));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::board::annotation::MarkColor;
+
+    /// Plays `moves`, given as origin and destination squares, keeping the
+    /// board after each one as the game does.
+    fn play(variant: Variant, fen: Option<&str>, moves: &[(&str, &str)]) -> (Vec<Board>, Board) {
+        let mut board = match fen {
+            Some(fen) => Board::from_fen(variant, fen),
+            None => Board::headless(variant, variant.starting_position()),
+        };
+        let mut history = vec![board.frozen()];
+        for (from, to) in moves {
+            board
+                .try_move_piece(from.parse().unwrap(), to.parse().unwrap())
+                .unwrap();
+            history.push(board.frozen());
+        }
+        (history, board)
+    }
+
+    #[test]
+    fn fools_mate() {
+        let (history, live) = play(
+            Variant::Standard,
+            None,
+            &[("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")],
+        );
+        assert_eq!(
+            write(&history, &live),
+            "[Variant \"Standard\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1"
+        );
+    }
+
+    #[test]
+    fn marks_go_in_the_comment_after_the_move() {
+        let (mut history, live) = play(Variant::Standard, None, &[("e2", "e4"), ("e7", "e5")]);
+        let e4 = "e4".parse().unwrap();
+        history[1]
+            .annotations_mut()
+            .toggle_square(e4, MarkColor::Green);
+        history[1].annotations_mut().toggle_arrow(
+            "g1".parse().unwrap(),
+            "f3".parse().unwrap(),
+            MarkColor::Red,
+        );
+        history[0]
+            .annotations_mut()
+            .toggle_square(e4, MarkColor::Blue);
+        assert_eq!(
+            write(&history, &live),
+            "[Variant \"Standard\"]\n[Result \"*\"]\n\n\
+             { [%csl Be4] } 1. e4 { [%csl Ge4][%cal Rg1f3] } 1... e5 *"
+        );
+    }
+
+    #[test]
+    fn set_up_positions_carry_their_fen() {
+        let fen = "7k/4Q3/6K1/8/8/8/8/8 w - - 0 1";
+        let (history, live) = play(Variant::Standard, Some(fen), &[("e7", "f7")]);
+        assert_eq!(
+            write(&history, &live),
+            format!(
+                "[Variant \"Standard\"]\n[Result \"1/2-1/2\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n\
+                 1. Qf7 1/2-1/2"
+            )
+        );
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12";
+        let (history, live) = play(Variant::Standard, Some(fen), &[("e8", "d8")]);
+        assert!(write(&history, &live).ends_with("12... Kd8 *"));
+    }
+
+    #[test]
+    fn chess960_always_carries_its_fen() {
+        let (history, live) = play(Variant::Chess960(518), None, &[]);
+        let fen = history[0].position().to_fen(CastlingNotation::XFen);
+        assert_eq!(
+            write(&history, &live),
+            format!("[Variant \"Chess960\"]\n[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n*")
+        );
+    }
+
+    #[test]
+    fn variant_tags() {
+        for (variant, tag) in [
+            (Variant::ThreeCheck, "Three-check"),
+            (Variant::KingOfTheHill, "King of the Hill"),
+            (Variant::Crazyhouse, "Crazyhouse"),
+        ] {
+            let (history, live) = play(variant, None, &[]);
+            assert!(write(&history, &live).starts_with(&format!("[Variant \"{tag}\"]\n")));
+        }
+    }
+}
@@ -0,0 +1,85 @@
+pub mod editor;
+pub mod main_menu;
+pub mod pause;
+pub mod results;
+pub mod setup;
+
+use macroquad::texture::Texture2D;
+
+use super::{Context, theme::Themes};
+
+/// Resources shared by every scene.
+pub struct Assets {
+    pub themes: Themes,
+    pub move_sprite: Texture2D,
+}
+
+/// What the scene stack does after a scene's frame.
+pub enum Transition {
+    Stay,
+    /// Opens a scene over the current one.
+    Push(Box<dyn Scene>),
+    /// Closes the current scene.
+    Pop,
+    /// Goes back to the scene at the bottom of the stack, the main menu.
+    Home,
+    /// Goes back to the main menu and opens a scene over it.
+    Start(Box<dyn Scene>),
+}
+
+/// A screen of the application, such as a menu or the game.
+pub trait Scene {
+    /// Draws the scene and handles its input, for one frame.
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition;
+    /// Draws the scene while an overlay covers it, leaving the input alone.
+    fn draw_covered(&self, _assets: &Assets) {}
+    /// Whether the scene is drawn over the one below rather than replacing it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// Scenes opened on top of each other, only the topmost one running.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+}
+impl SceneStack {
+    pub fn new(root: Box<dyn Scene>) -> Self {
+        Self { scenes: vec![root] }
+    }
+    pub fn push(&mut self, scene: Box<dyn Scene>) {
+        self.scenes.push(scene);
+    }
+    /// Whether every scene was closed, ending the application.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Runs a frame of the topmost scene, drawing the scenes it covers first
+    /// when it is an overlay.
+    pub fn frame(&mut self, ctx: &Context, assets: &mut Assets) {
+        let Some(top) = self.scenes.len().checked_sub(1) else {
+            return;
+        };
+        let mut base = top;
+        while base > 0 && self.scenes[base].is_overlay() {
+            base -= 1;
+        }
+        for scene in &self.scenes[base..top] {
+            scene.draw_covered(assets);
+        }
+        match self.scenes[top].frame(ctx, assets) {
+            Transition::Stay => (),
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Home => self.scenes.truncate(1),
+            Transition::Start(scene) => {
+                self.scenes.truncate(1);
+                self.scenes.push(scene);
+            }
+        }
+    }
+}
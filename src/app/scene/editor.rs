@@ -0,0 +1,437 @@
+use macroquad::{
+    color::{DARKGRAY, LIGHTGRAY, ORANGE, WHITE, YELLOW},
+    input::{MouseButton, is_mouse_button_pressed, is_mouse_button_released},
+    math::{Rect, Vec2},
+    miniquad::window::clipboard_set,
+    shapes::{draw_rectangle, draw_rectangle_lines},
+};
+use tracing::info;
+
+use super::{
+    Assets, Scene, Transition,
+    setup::{SetupDialog, VARIANTS},
+};
+use crate::app::{
+    Context, Game, GameSetup,
+    board::{
+        Board, GridPosition,
+        castling::CastlingSide,
+        fen::{CastlingNotation, Position},
+        highlight::HighlightLayers,
+        piece::{Piece, PieceColor, PieceKind},
+        player::sprites_for,
+        setup,
+        sprites::SpritesMap,
+        variant::Variant,
+    },
+    ui::Ui,
+};
+
+/// Pieces offered by the palette, in order.
+const KINDS: [PieceKind; 8] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+    PieceKind::King,
+    PieceKind::Archbishop,
+    PieceKind::Chancellor,
+];
+const PALETTE_TILE: f32 = 64.0;
+
+/// Scene setting up a position piece by piece, to be played or exported.
+pub struct Editor {
+    /// Index in [`VARIANTS`].
+    variant: usize,
+    position: Position,
+    /// The position as drawn, set up again after each edit.
+    board: Board,
+    white_sprites: SpritesMap,
+    black_sprites: SpritesMap,
+    /// Piece placed by clicking a square, picked in the palette.
+    brush: Option<(PieceKind, PieceColor)>,
+    /// Piece being dragged, placed on the square it is released over.
+    held: Option<(PieceKind, PieceColor)>,
+    /// What the last export did.
+    status: Option<&'static str>,
+}
+impl Editor {
+    pub fn new(assets: &Assets) -> Self {
+        let variant: Variant = VARIANTS[0].parse().expect("The variants offered parse");
+        let position = variant.starting_position();
+        let (white_sprites, black_sprites) =
+            assets.themes.piece_set().sprites(SpritesMap::TILE_SIZE);
+        let board = Board::from_position(
+            variant,
+            position.clone(),
+            white_sprites.clone(),
+            black_sprites.clone(),
+            assets.move_sprite.clone(),
+        );
+        let mut editor = Self {
+            variant: 0,
+            position,
+            board,
+            white_sprites,
+            black_sprites,
+            brush: None,
+            held: None,
+            status: None,
+        };
+        editor.reset(editor.position.clone(), assets);
+        editor
+    }
+    fn row_rect(idx: usize) -> Rect {
+        Rect {
+            x: 1080.0,
+            y: 220.0 + idx as f32 * 68.0,
+            w: 800.0,
+            h: 56.0,
+        }
+    }
+    /// One of the two rows of three buttons below the palette.
+    fn button_rect(row: usize, column: usize) -> Rect {
+        Rect {
+            x: 1080.0 + column as f32 * 272.0,
+            y: 920.0 + row as f32 * 68.0,
+            w: 256.0,
+            h: 56.0,
+        }
+    }
+    fn palette_rect(row: usize, column: usize) -> Rect {
+        Rect {
+            x: 1080.0 + column as f32 * PALETTE_TILE,
+            y: 500.0 + row as f32 * PALETTE_TILE,
+            w: PALETTE_TILE,
+            h: PALETTE_TILE,
+        }
+    }
+
+    /// Starts over from `position`, with sprites sized for its board.
+    fn reset(&mut self, position: Position, assets: &Assets) {
+        self.position = position;
+        self.refresh(assets);
+        (self.white_sprites, self.black_sprites) = assets
+            .themes
+            .piece_set()
+            .sprites(self.board.cell_size().min_element());
+        self.refresh(assets);
+    }
+    /// Sets the board up again after the position was edited.
+    fn refresh(&mut self, assets: &Assets) {
+        self.position.retain_valid_castling();
+        self.position.en_passant = None;
+        self.board = Board::from_position(
+            self.board.variant(),
+            self.position.clone(),
+            self.white_sprites.clone(),
+            self.black_sprites.clone(),
+            assets.move_sprite.clone(),
+        );
+        self.board.set_theme(
+            assets.themes.palette(),
+            &self.white_sprites,
+            &self.black_sprites,
+        );
+    }
+    /// Removes the piece at `square`, if any.
+    fn take(&mut self, square: GridPosition) -> Option<(PieceKind, PieceColor)> {
+        let idx = self
+            .position
+            .pieces
+            .iter()
+            .position(|p| p.position == square)?;
+        let piece = self.position.pieces.swap_remove(idx);
+        Some((piece.kind, piece.color))
+    }
+    /// Places a piece at `square`, replacing the one standing there.
+    fn put(&mut self, square: GridPosition, (kind, color): (PieceKind, PieceColor)) {
+        self.take(square);
+        self.position.pieces.push(Piece {
+            kind,
+            color,
+            position: square,
+            promoted: false,
+        });
+    }
+
+    /// Edits the position with the mouse, over the board.
+    fn edit(&mut self, mouse: Vec2, assets: &Assets) {
+        let square = self
+            .board
+            .grid_from_world(mouse)
+            .filter(|&p| self.position.shape.is_playable(p));
+        if is_mouse_button_pressed(MouseButton::Left)
+            && let Some(square) = square
+        {
+            let taken = self.take(square);
+            match self.brush {
+                // Clicking a piece with its own brush clears the square.
+                Some(brush) if taken != Some(brush) => self.put(square, brush),
+                Some(_) => (),
+                None => self.held = taken,
+            }
+            self.refresh(assets);
+        }
+        if is_mouse_button_pressed(MouseButton::Right)
+            && let Some(square) = square
+        {
+            self.take(square);
+            self.refresh(assets);
+        }
+        // Pieces released away from the board are discarded.
+        if is_mouse_button_released(MouseButton::Left)
+            && let Some(held) = self.held.take()
+        {
+            if let Some(square) = square {
+                self.put(square, held);
+            }
+            self.refresh(assets);
+        }
+    }
+    /// Draws a piece of every kind for each side, picking the brush when
+    /// clicked.
+    fn palette(&mut self, mouse: Vec2) {
+        let colors = self.board.variant().colors();
+        for (row, &color) in colors.iter().enumerate() {
+            let (sprites, tint) = sprites_for(color, &self.black_sprites, &self.white_sprites);
+            for (column, &kind) in KINDS.iter().enumerate() {
+                let rect = Self::palette_rect(row, column);
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, DARKGRAY);
+                if self.brush == Some((kind, color)) {
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 4.0, YELLOW);
+                } else {
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, LIGHTGRAY);
+                }
+                sprites.draw_piece(kind, rect.point(), rect.size(), tint);
+                if is_mouse_button_pressed(MouseButton::Left) && rect.contains(mouse) {
+                    self.brush = match self.brush {
+                        Some(brush) if brush == (kind, color) => None,
+                        _ => Some((kind, color)),
+                    };
+                    self.held = self.brush;
+                }
+            }
+        }
+    }
+    fn export(&mut self, text: &str, status: &'static str) {
+        info!("{text}");
+        clipboard_set(text);
+        self.status = Some(status);
+    }
+}
+impl Scene for Editor {
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition {
+        let mouse = ctx.mouse_position;
+        self.board.render(&HighlightLayers::default());
+        let ui = Ui::new(ctx);
+        let variant = self.board.variant();
+
+        let step = ui.choice(Self::row_rect(0), "Variant", VARIANTS[self.variant]);
+        if step != 0 {
+            self.variant = SetupDialog::cycle(self.variant, step, VARIANTS.len());
+            let variant: Variant = VARIANTS[self.variant]
+                .parse()
+                .expect("The variants offered parse");
+            self.board = Board::from_position(
+                variant,
+                variant.starting_position(),
+                self.white_sprites.clone(),
+                self.black_sprites.clone(),
+                assets.move_sprite.clone(),
+            );
+            self.reset(variant.starting_position(), assets);
+            return Transition::Stay;
+        }
+
+        let colors = variant.colors();
+        // FEN, setup files and castling rights only tell White and Black apart.
+        let two_sides = colors.len() == 2;
+        let turn = colors
+            .iter()
+            .position(|&c| c == self.position.turn)
+            .unwrap_or_default();
+        let step = ui.choice(
+            Self::row_rect(1),
+            "To move",
+            &format!("{:?}", self.position.turn),
+        );
+        if step != 0 {
+            self.position.turn = colors[SetupDialog::cycle(turn, step, colors.len())];
+            self.refresh(assets);
+        }
+
+        if variant.has_castling() && two_sides {
+            let sides = [
+                (PieceColor::White, CastlingSide::King),
+                (PieceColor::White, CastlingSide::Queen),
+                (PieceColor::Black, CastlingSide::King),
+                (PieceColor::Black, CastlingSide::Queen),
+            ];
+            for (idx, (color, side)) in sides.into_iter().enumerate() {
+                let row = Self::row_rect(2 + idx / 2);
+                let rect = Rect {
+                    x: row.x + (idx % 2) as f32 * (row.w / 2.0 + 8.0),
+                    w: row.w / 2.0 - 8.0,
+                    ..row
+                };
+                let name = match side {
+                    CastlingSide::King => "O-O",
+                    CastlingSide::Queen => "O-O-O",
+                };
+                let state = match self.position.castling.get(color, side) {
+                    Some(_) => "on",
+                    None => "off",
+                };
+                if ui.button(rect, &format!("{color:?} {name}: {state}"), true) {
+                    self.position.toggle_castling(color, side);
+                    self.refresh(assets);
+                }
+            }
+        }
+
+        self.palette(mouse);
+
+        let warnings = warnings(&self.position, &self.board);
+        for (idx, warning) in warnings.iter().enumerate() {
+            ui.label(warning, 1480.0, 800.0 + idx as f32 * 32.0, 32.0, ORANGE);
+        }
+
+        if ui.button(Self::button_rect(0, 0), "Clear", true) {
+            let empty = Position {
+                pieces: vec![],
+                pockets: vec![],
+                castling: Default::default(),
+                ..self.position.clone()
+            };
+            self.reset(empty, assets);
+        }
+        if ui.button(Self::button_rect(0, 1), "Start position", true) {
+            self.reset(variant.starting_position(), assets);
+        }
+        if ui.button(Self::button_rect(0, 2), "Back", true) {
+            return Transition::Pop;
+        }
+        if ui.button(Self::button_rect(1, 0), "Export FEN", two_sides) {
+            let fen = self.position.to_fen(CastlingNotation::default());
+            self.export(&fen, "FEN copied to the clipboard");
+        }
+        if ui.button(Self::button_rect(1, 1), "Export setup", two_sides) {
+            let setup = setup::write(&self.position);
+            self.export(&setup, "Setup copied to the clipboard");
+        }
+        if ui.button(Self::button_rect(1, 2), "Play", warnings.is_empty()) {
+            let setup = GameSetup {
+                variant,
+                position: Some(self.position.clone()),
+                time_control: None,
+                color: self.position.turn,
+            };
+            let game = Game::new(setup, &assets.themes, assets.move_sprite.clone());
+            return Transition::Start(Box::new(game));
+        }
+        if let Some(status) = self.status {
+            ui.label(status, 1480.0, 1070.0, 28.0, WHITE);
+        }
+
+        self.edit(mouse, assets);
+        if let Some((kind, color)) = self.held {
+            let (sprites, tint) = sprites_for(color, &self.black_sprites, &self.white_sprites);
+            let size = self.board.cell_size();
+            sprites.draw_piece(kind, mouse - size / 2.0, size, tint);
+        }
+        Transition::Stay
+    }
+}
+
+/// Reasons `position`, set up on `board`, can't be played from.
+fn warnings(position: &Position, board: &Board) -> Vec<String> {
+    let variant = board.variant();
+    let num_cells = position.num_cells();
+    let mut res = vec![];
+    for &color in variant.colors() {
+        // What the variant starts with tells which sides need a king and
+        // whether pawns may stand on their first rank, as in Horde.
+        let starting = variant.starting_pieces(color);
+        let rank = |p: &Piece| color.relative_rank(p.position, num_cells);
+        let pieces = || position.pieces.iter().filter(|p| p.color == color);
+        if starting.iter().any(|p| p.kind == PieceKind::King)
+            && !pieces().any(|p| p.kind == PieceKind::King)
+        {
+            res.push(format!("{color:?} has no king"));
+        }
+        let first_rank_pawns = starting
+            .iter()
+            .any(|p| p.kind == PieceKind::Pawn && rank(p) == 0);
+        let last_rank = color.depth(num_cells) - 1;
+        if pieces().any(|p| {
+            p.kind == PieceKind::Pawn && (rank(p) == last_rank || rank(p) == 0 && !first_rank_pawns)
+        }) {
+            res.push(format!("{color:?} has pawns on a back rank"));
+        }
+        if color != position.turn && board.in_check(color) {
+            res.push(format!("{color:?} is in check without being to move"));
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warnings_for(variant: Variant, fen: &str) -> Vec<String> {
+        let position: Position = fen.parse().unwrap();
+        warnings(&position, &Board::headless(variant, position.clone()))
+    }
+
+    #[test]
+    fn starting_positions_are_playable() {
+        for variant in [Variant::Standard, Variant::Horde, Variant::Antichess] {
+            let position = variant.starting_position();
+            let board = Board::headless(variant, position.clone());
+            assert_eq!(warnings(&position, &board), Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn missing_king() {
+        let warnings = warnings_for(Variant::Standard, "4k3/8/8/8/8/8/8/8 w - - 0 1");
+        assert_eq!(warnings, ["White has no king"]);
+        // The Horde has no king to lose.
+        let warnings = warnings_for(Variant::Horde, "4k3/8/8/8/8/8/8/P7 w - - 0 1");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn pawns_on_a_back_rank() {
+        let fen = "4k2P/8/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            warnings_for(Variant::Standard, fen),
+            ["White has pawns on a back rank"]
+        );
+        let fen = "4k3/8/8/8/8/8/8/P3K2p w - - 0 1";
+        assert_eq!(
+            warnings_for(Variant::Standard, fen),
+            [
+                "White has pawns on a back rank",
+                "Black has pawns on a back rank"
+            ]
+        );
+        // The Horde starts with pawns on its first rank.
+        let fen = "4k3/8/8/8/8/8/8/PPPP4 w - - 0 1";
+        assert!(warnings_for(Variant::Horde, fen).is_empty());
+    }
+
+    #[test]
+    fn side_not_to_move_in_check() {
+        let fen = "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            warnings_for(Variant::Standard, fen),
+            ["Black is in check without being to move"]
+        );
+        let fen = "4k3/4R3/8/8/8/8/8/4K3 b - - 0 1";
+        assert!(warnings_for(Variant::Standard, fen).is_empty());
+    }
+}
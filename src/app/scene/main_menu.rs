@@ -0,0 +1,33 @@
+use macroquad::{color::WHITE, math::Rect};
+
+use super::{Assets, Scene, Transition, editor::Editor, setup::SetupDialog};
+use crate::app::{Context, ui::Ui};
+
+/// First screen, leading to a new game or the board editor.
+pub struct MainMenu;
+impl MainMenu {
+    fn button_rect(idx: usize) -> Rect {
+        Rect {
+            x: 760.0,
+            y: 420.0 + idx as f32 * 100.0,
+            w: 400.0,
+            h: 72.0,
+        }
+    }
+}
+impl Scene for MainMenu {
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition {
+        let ui = Ui::new(ctx);
+        ui.label("Chetro", 960.0, 300.0, 120.0, WHITE);
+        if ui.button(Self::button_rect(0), "New game", true) {
+            return Transition::Push(Box::new(SetupDialog::default()));
+        }
+        if ui.button(Self::button_rect(1), "Board editor", true) {
+            return Transition::Push(Box::new(Editor::new(assets)));
+        }
+        if ui.button(Self::button_rect(2), "Quit", true) {
+            return Transition::Pop;
+        }
+        Transition::Stay
+    }
+}
@@ -0,0 +1,40 @@
+use macroquad::{
+    color::WHITE,
+    input::{KeyCode, is_key_pressed},
+    math::Rect,
+};
+
+use super::{Assets, Scene, Transition};
+use crate::app::{Context, ui::Ui};
+
+/// Overlay stopping the game, clocks included, until resumed.
+pub struct PauseOverlay;
+impl Scene for PauseOverlay {
+    fn frame(&mut self, ctx: &Context, _assets: &mut Assets) -> Transition {
+        let ui = Ui::new(ctx);
+        ui.dim();
+        ui.panel(Rect {
+            x: 660.0,
+            y: 340.0,
+            w: 600.0,
+            h: 400.0,
+        });
+        ui.label("Paused", 960.0, 440.0, 80.0, WHITE);
+        let button = |idx: usize| Rect {
+            x: 760.0,
+            y: 500.0 + idx as f32 * 100.0,
+            w: 400.0,
+            h: 72.0,
+        };
+        if ui.button(button(0), "Resume", true) || is_key_pressed(KeyCode::Escape) {
+            return Transition::Pop;
+        }
+        if ui.button(button(1), "Main menu", true) {
+            return Transition::Home;
+        }
+        Transition::Stay
+    }
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,55 @@
+use macroquad::{color::WHITE, math::Rect};
+
+use super::{Assets, Scene, Transition};
+use crate::app::{Context, Game, GameSetup, board::rules::GameResult, ui::Ui};
+
+/// Overlay announcing how a game ended, from which it can be replayed.
+pub struct ResultsOverlay {
+    result: GameResult,
+    /// Setup of the game that ended.
+    setup: GameSetup,
+}
+impl ResultsOverlay {
+    pub fn new(result: GameResult, setup: GameSetup) -> Self {
+        Self { result, setup }
+    }
+}
+impl Scene for ResultsOverlay {
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition {
+        let ui = Ui::new(ctx);
+        ui.dim();
+        ui.panel(Rect {
+            x: 560.0,
+            y: 290.0,
+            w: 800.0,
+            h: 500.0,
+        });
+        ui.label("Game over", 960.0, 390.0, 80.0, WHITE);
+        ui.label(&self.result.to_string(), 960.0, 450.0, 40.0, WHITE);
+        let button = |idx: usize| Rect {
+            x: 760.0,
+            y: 490.0 + idx as f32 * 96.0,
+            w: 400.0,
+            h: 72.0,
+        };
+        if ui.button(button(0), "Rematch", true) {
+            let game = Game::new(
+                self.setup.rematch(),
+                &assets.themes,
+                assets.move_sprite.clone(),
+            );
+            return Transition::Start(Box::new(game));
+        }
+        // Closing the overlay leaves the finished game to be looked through.
+        if ui.button(button(1), "Review", true) {
+            return Transition::Pop;
+        }
+        if ui.button(button(2), "Main menu", true) {
+            return Transition::Home;
+        }
+        Transition::Stay
+    }
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
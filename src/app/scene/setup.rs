@@ -0,0 +1,129 @@
+use macroquad::{color::WHITE, math::Rect};
+
+use super::{Assets, Scene, Transition};
+use crate::app::{
+    Context, Game, GameSetup,
+    board::{piece::PieceColor, variant::Variant},
+    ui::Ui,
+};
+
+/// Names of the variants offered, as [`Variant`] parses them.
+pub(super) const VARIANTS: [&str; 15] = [
+    "standard",
+    "chess960",
+    "capablanca",
+    "grand",
+    "crazyhouse",
+    "atomic",
+    "kingofthehill",
+    "threecheck",
+    "antichess",
+    "horde",
+    "fogofwar",
+    "duck",
+    "shogihybrid",
+    "fourplayer",
+    "fourplayer:lks",
+];
+/// Time controls offered, as [`crate::app::clock::TimeControl`] parses them,
+/// after playing without a clock.
+const TIME_CONTROLS: [&str; 7] = [
+    "1+0",
+    "3+2",
+    "5+3",
+    "10+5",
+    "15+10",
+    "30+0",
+    "40/90+30:30+30",
+];
+/// Dialog choosing the variant, time control and color of a new hot-seat
+/// game.
+#[derive(Default)]
+pub struct SetupDialog {
+    variant: usize,
+    /// Index in [`TIME_CONTROLS`] plus one, zero playing without a clock.
+    time_control: usize,
+    /// Index in the variant's colors plus one, zero picking one at random.
+    color: usize,
+}
+impl SetupDialog {
+    fn row_rect(idx: usize) -> Rect {
+        Rect {
+            x: 560.0,
+            y: 300.0 + idx as f32 * 96.0,
+            w: 800.0,
+            h: 72.0,
+        }
+    }
+    /// Index `step` away from `idx` among `len` options, wrapping around.
+    pub(super) fn cycle(idx: usize, step: isize, len: usize) -> usize {
+        (idx as isize + step).rem_euclid(len as isize) as usize
+    }
+    fn setup(&self) -> GameSetup {
+        let variant: Variant = VARIANTS[self.variant]
+            .parse()
+            .expect("The variants offered parse");
+        let colors = variant.colors();
+        let color = match self.color.checked_sub(1) {
+            Some(idx) => colors[idx.min(colors.len() - 1)],
+            None => colors[macroquad::rand::gen_range(0, colors.len())],
+        };
+        GameSetup {
+            variant,
+            position: None,
+            time_control: self.time_control.checked_sub(1).map(|idx| {
+                TIME_CONTROLS[idx]
+                    .parse()
+                    .expect("The time controls offered parse")
+            }),
+            color,
+        }
+    }
+}
+impl Scene for SetupDialog {
+    fn frame(&mut self, ctx: &Context, assets: &mut Assets) -> Transition {
+        let ui = Ui::new(ctx);
+        ui.label("New game", 960.0, 220.0, 80.0, WHITE);
+
+        let step = ui.choice(Self::row_rect(0), "Variant", VARIANTS[self.variant]);
+        self.variant = Self::cycle(self.variant, step, VARIANTS.len());
+        // The color may not exist in the new variant.
+        let colors: &[PieceColor] = match VARIANTS[self.variant].parse::<Variant>() {
+            Ok(variant) => variant.colors(),
+            Err(_) => &[],
+        };
+        self.color = self.color.min(colors.len());
+
+        let time_control = match self.time_control.checked_sub(1) {
+            Some(idx) => TIME_CONTROLS[idx],
+            None => "none",
+        };
+        let step = ui.choice(Self::row_rect(1), "Time control", time_control);
+        self.time_control = Self::cycle(self.time_control, step, TIME_CONTROLS.len() + 1);
+
+        let color = match self.color.checked_sub(1) {
+            Some(idx) => format!("{:?}", colors[idx]),
+            None => "random".to_string(),
+        };
+        let step = ui.choice(Self::row_rect(2), "Color", &color);
+        self.color = Self::cycle(self.color, step, colors.len() + 1);
+
+        let buttons = Self::row_rect(4);
+        let back = Rect {
+            w: buttons.w / 2.0 - 16.0,
+            ..buttons
+        };
+        let start = Rect {
+            x: back.right() + 32.0,
+            ..back
+        };
+        if ui.button(back, "Back", true) {
+            return Transition::Pop;
+        }
+        if ui.button(start, "Start", true) {
+            let game = Game::new(self.setup(), &assets.themes, assets.move_sprite.clone());
+            return Transition::Start(Box::new(game));
+        }
+        Transition::Stay
+    }
+}
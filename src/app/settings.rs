@@ -0,0 +1,152 @@
+use macroquad::{
+    color::{Color, LIGHTGRAY, WHITE},
+    math::{Rect, Vec2},
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use super::{
+    board::{highlight::HighlightLayers, piece::PieceColor},
+    theme::Themes,
+};
+
+/// Panel listing the options that can be switched during a game, opened with
+/// its button and closed with it or Escape.
+pub struct SettingsMenu {
+    button: Rect,
+    rect: Rect,
+    open: bool,
+}
+/// What a row of the menu shows, and switches when clicked.
+#[derive(Clone, Copy)]
+enum Row {
+    Heading(&'static str),
+    LastMove,
+    Check,
+    Hanging,
+    Attacks,
+    PieceSet,
+    Palette,
+}
+impl Row {
+    const ALL: [Row; 8] = [
+        Row::Heading("Highlights"),
+        Row::LastMove,
+        Row::Check,
+        Row::Hanging,
+        Row::Attacks,
+        Row::Heading("Theme"),
+        Row::PieceSet,
+        Row::Palette,
+    ];
+
+    fn label(self, layers: &HighlightLayers, themes: &Themes) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        match self {
+            Row::Heading(title) => title.to_string(),
+            Row::LastMove => format!("Last move: {}", on_off(layers.last_move)),
+            Row::Check => format!("Check: {}", on_off(layers.check)),
+            Row::Hanging => format!("Hanging pieces: {}", on_off(layers.hanging)),
+            Row::Attacks => match layers.attacks {
+                Some(color) => format!("Attacks: {color:?}"),
+                None => "Attacks: off".to_string(),
+            },
+            Row::PieceSet => format!("Pieces: {}", themes.piece_set().name),
+            Row::Palette => format!("Board: {}", themes.palette().name),
+        }
+    }
+    fn toggle(self, layers: &mut HighlightLayers, themes: &mut Themes, colors: &[PieceColor]) {
+        match self {
+            Row::Heading(_) => (),
+            Row::LastMove => layers.last_move = !layers.last_move,
+            Row::Check => layers.check = !layers.check,
+            Row::Hanging => layers.hanging = !layers.hanging,
+            Row::Attacks => layers.cycle_attacks(colors),
+            Row::PieceSet => themes.next_piece_set(),
+            Row::Palette => themes.next_palette(),
+        }
+    }
+}
+impl SettingsMenu {
+    const ROW_HEIGHT: f32 = 48.0;
+    const FONT_SIZE: f32 = 36.0;
+
+    pub fn new(button: Rect, rect: Rect) -> Self {
+        Self {
+            button,
+            rect,
+            open: false,
+        }
+    }
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+    fn row_rect(&self, idx: usize) -> Rect {
+        Rect {
+            x: self.rect.x,
+            y: self.rect.y + idx as f32 * Self::ROW_HEIGHT,
+            w: self.rect.w,
+            h: Self::ROW_HEIGHT,
+        }
+    }
+
+    /// Handles a click at `pos`, returning whether the menu took it. While
+    /// open, the menu takes every click, closing when one lands outside it.
+    pub fn click(
+        &mut self,
+        pos: Vec2,
+        layers: &mut HighlightLayers,
+        themes: &mut Themes,
+        colors: &[PieceColor],
+    ) -> bool {
+        if self.button.contains(pos) {
+            self.toggle();
+            return true;
+        }
+        if !self.open {
+            return false;
+        }
+        match (0..Row::ALL.len()).find(|&idx| self.row_rect(idx).contains(pos)) {
+            Some(idx) => Row::ALL[idx].toggle(layers, themes, colors),
+            None if !self.rect.contains(pos) => self.open = false,
+            None => (),
+        }
+        true
+    }
+
+    pub fn draw(&self, layers: &HighlightLayers, themes: &Themes) {
+        let background = Color {
+            r: 0.15,
+            g: 0.15,
+            b: 0.15,
+            a: 1.0,
+        };
+        let Rect { x, y, w, h } = self.button;
+        draw_rectangle(x, y, w, h, background);
+        draw_text("Settings", x + 8.0, y + h - 12.0, Self::FONT_SIZE, WHITE);
+        if !self.open {
+            return;
+        }
+        let Rect { x, y, w, h } = self.rect;
+        draw_rectangle(x, y, w, h, background);
+        draw_rectangle_lines(x, y, w, h, 2.0, LIGHTGRAY);
+        for (idx, row) in Row::ALL.iter().enumerate() {
+            let rect = self.row_rect(idx);
+            let (indent, color) = match row {
+                Row::Heading(_) => (8.0, LIGHTGRAY),
+                _ => (24.0, WHITE),
+            };
+            draw_text(
+                &row.label(layers, themes),
+                rect.x + indent,
+                rect.y + rect.h - 12.0,
+                Self::FONT_SIZE,
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use macroquad::{
+    color::Color,
+    texture::{Texture2D, load_texture},
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::board::{
+    palette::BoardPalette,
+    sprites::{PieceMappings, SpritesMap},
+    svg::SvgPieces,
+};
+
+/// Where the sprites of a piece set come from.
+enum PieceSource {
+    /// PNG atlases, the same one for both sides when it holds both.
+    Atlases {
+        white: SpritesMap,
+        black: SpritesMap,
+    },
+    /// Rasterised again for each board, at the size of its squares.
+    Svg(Box<SvgPieces>),
+}
+
+/// Sprites of both sides, loaded from a directory holding either
+/// `pieces.json` and its atlases or one SVG file per piece.
+pub struct PieceSet {
+    pub name: String,
+    source: PieceSource,
+}
+impl PieceSet {
+    /// The atlases are `white.png` and `black.png`, or `pieces.png` alone when
+    /// the mappings give Black's row. See [`SvgPieces::load`] for SVG sets.
+    async fn load(dir: &Path) -> Result<Self, String> {
+        let mappings_path = dir.join("pieces.json");
+        let source = if mappings_path.exists() {
+            let json = std::fs::read_to_string(mappings_path).map_err(|e| e.to_string())?;
+            let mappings: PieceMappings = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            let (white, black) = if mappings.black_row.is_some() {
+                SpritesMap::shared(load_image(&dir.join("pieces.png")).await?, mappings)
+            } else {
+                (
+                    SpritesMap::new(load_image(&dir.join("white.png")).await?, mappings),
+                    SpritesMap::new(load_image(&dir.join("black.png")).await?, mappings),
+                )
+            };
+            PieceSource::Atlases { white, black }
+        } else {
+            PieceSource::Svg(Box::new(SvgPieces::load(dir)?))
+        };
+        Ok(Self {
+            name: dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            source,
+        })
+    }
+    /// White's and Black's sprites for squares of `cell_size` pixels.
+    pub fn sprites(&self, cell_size: f32) -> (SpritesMap, SpritesMap) {
+        match &self.source {
+            PieceSource::Atlases { white, black } => (white.clone(), black.clone()),
+            PieceSource::Svg(svg) => svg.rasterize(cell_size),
+        }
+    }
+}
+
+async fn load_image(path: &Path) -> Result<Texture2D, String> {
+    let path = path.to_string_lossy();
+    load_texture(&path).await.map_err(|e| e.to_string())
+}
+
+/// A [`BoardPalette`] as written in `boards.json`, with RGB colors and the
+/// texture's path relative to the themes directory.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaletteFile {
+    name: String,
+    light: [u8; 3],
+    dark: [u8; 3],
+    wall: [u8; 3],
+    texture: Option<String>,
+}
+
+/// Every piece set and board palette available, and the ones in use.
+pub struct Themes {
+    piece_sets: Vec<PieceSet>,
+    palettes: Vec<BoardPalette>,
+    piece_set: usize,
+    palette: usize,
+}
+impl Themes {
+    /// Loads the piece sets from the subdirectories of `dir/pieces` and the
+    /// palettes listed in `dir/boards.json`. Themes that fail to load are
+    /// skipped, but at least one piece set is needed.
+    pub async fn load(dir: &Path) -> Self {
+        let mut set_dirs: Vec<PathBuf> = std::fs::read_dir(dir.join("pieces"))
+            .expect("Unreadable piece sets directory")
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        set_dirs.sort();
+        let mut piece_sets = vec![];
+        for set_dir in set_dirs {
+            match PieceSet::load(&set_dir).await {
+                Ok(set) => piece_sets.push(set),
+                Err(err) => warn!("Skipping piece set {}: {err}", set_dir.display()),
+            }
+        }
+        assert!(!piece_sets.is_empty(), "No piece set could be loaded");
+
+        let files: Vec<PaletteFile> = std::fs::read_to_string(dir.join("boards.json"))
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+            .unwrap_or_else(|err| {
+                warn!("Using the default board palette: {err}");
+                vec![]
+            });
+        let mut palettes = vec![];
+        for file in files {
+            let texture = match &file.texture {
+                Some(path) => match load_image(&dir.join(path)).await {
+                    Ok(texture) => Some(texture),
+                    Err(err) => {
+                        warn!("Skipping board texture {path}: {err}");
+                        None
+                    }
+                },
+                None => None,
+            };
+            let rgb = |[r, g, b]: [u8; 3]| Color::from_rgba(r, g, b, 255);
+            palettes.push(BoardPalette {
+                name: file.name,
+                light: rgb(file.light),
+                dark: rgb(file.dark),
+                wall: rgb(file.wall),
+                texture,
+            });
+        }
+        if palettes.is_empty() {
+            palettes.push(BoardPalette::default());
+        }
+        Self {
+            piece_sets,
+            palettes,
+            piece_set: 0,
+            palette: 0,
+        }
+    }
+
+    #[inline]
+    pub fn piece_set(&self) -> &PieceSet {
+        &self.piece_sets[self.piece_set]
+    }
+    #[inline]
+    pub fn palette(&self) -> &BoardPalette {
+        &self.palettes[self.palette]
+    }
+    pub fn next_piece_set(&mut self) {
+        self.piece_set = (self.piece_set + 1) % self.piece_sets.len();
+    }
+    pub fn next_palette(&mut self) {
+        self.palette = (self.palette + 1) % self.palettes.len();
+    }
+
+    /// Switches to the themes named in `settings`, keeping the current ones
+    /// for names that aren't found.
+    pub fn select(&mut self, settings: &ThemeSettings) {
+        if let Some(idx) = self
+            .piece_sets
+            .iter()
+            .position(|s| Some(&s.name) == settings.piece_set.as_ref())
+        {
+            self.piece_set = idx;
+        }
+        if let Some(idx) = self
+            .palettes
+            .iter()
+            .position(|p| Some(&p.name) == settings.palette.as_ref())
+        {
+            self.palette = idx;
+        }
+    }
+    /// Names of the themes in use, to be saved.
+    pub fn settings(&self) -> ThemeSettings {
+        ThemeSettings {
+            piece_set: Some(self.piece_set().name.clone()),
+            palette: Some(self.palette().name.clone()),
+        }
+    }
+}
+
+/// Themes chosen by the user, kept in `settings.json` in the user's
+/// configuration directory.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub piece_set: Option<String>,
+    pub palette: Option<String>,
+}
+impl ThemeSettings {
+    fn path() -> Option<PathBuf> {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config.join("chetro").join("settings.json"))
+    }
+    /// The saved settings, or the defaults when there are none.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+                warn!("Ignoring invalid settings {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| {
+                let json = serde_json::to_string_pretty(self).expect("Settings serialize to JSON");
+                std::fs::write(&path, json)
+            });
+        if let Err(err) = saved {
+            warn!("Couldn't save settings to {}: {err}", path.display());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use macroquad::{
+    color::{Color, DARKGRAY, GRAY, LIGHTGRAY, WHITE},
+    input::{MouseButton, is_mouse_button_pressed},
+    math::{Rect, Vec2},
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    text::{draw_text, measure_text},
+};
+
+use super::Context;
+
+const FONT_SIZE: f32 = 40.0;
+const PANEL: Color = Color {
+    r: 0.15,
+    g: 0.15,
+    b: 0.15,
+    a: 1.0,
+};
+const HOVERED: Color = Color {
+    r: 0.3,
+    g: 0.45,
+    b: 0.7,
+    a: 1.0,
+};
+
+/// Immediate-mode widgets, each drawn and checked for clicks in the same call.
+pub struct Ui {
+    mouse: Vec2,
+    clicked: bool,
+}
+impl Ui {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            mouse: ctx.mouse_position,
+            clicked: is_mouse_button_pressed(MouseButton::Left),
+        }
+    }
+
+    /// Darkens the whole canvas, for overlays.
+    pub fn dim(&self) {
+        draw_rectangle(
+            0.0,
+            0.0,
+            1920.0,
+            1080.0,
+            Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.6,
+            },
+        );
+    }
+    pub fn panel(&self, rect: Rect) {
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, PANEL);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, LIGHTGRAY);
+    }
+    /// Draws `text` centered horizontally on `center_x`, with its baseline at
+    /// `y`.
+    pub fn label(&self, text: &str, center_x: f32, y: f32, font_size: f32, color: Color) {
+        let width = measure_text(text, None, font_size as u16, 1.0).width;
+        draw_text(text, center_x - width / 2.0, y, font_size, color);
+    }
+    /// A button returning whether it was clicked. Disabled buttons are
+    /// grayed out and never clicked.
+    pub fn button(&self, rect: Rect, text: &str, enabled: bool) -> bool {
+        let hovered = enabled && rect.contains(self.mouse);
+        let background = if hovered { HOVERED } else { PANEL };
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, background);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, LIGHTGRAY);
+        let color = if enabled { WHITE } else { GRAY };
+        self.label(
+            text,
+            rect.center().x,
+            rect.y + (rect.h + FONT_SIZE) / 2.0 - 8.0,
+            FONT_SIZE,
+            color,
+        );
+        hovered && self.clicked
+    }
+    /// A `name: value` row with arrows on both ends, returning `-1` or `1`
+    /// when an arrow is clicked to pick the previous or next value.
+    pub fn choice(&self, rect: Rect, name: &str, value: &str) -> isize {
+        let arrow = |x| Rect {
+            x,
+            y: rect.y,
+            w: rect.h,
+            h: rect.h,
+        };
+        let previous = self.button(arrow(rect.x), "<", true);
+        let next = self.button(arrow(rect.right() - rect.h), ">", true);
+        draw_rectangle(
+            rect.x + rect.h,
+            rect.y,
+            rect.w - 2.0 * rect.h,
+            rect.h,
+            DARKGRAY,
+        );
+        self.label(
+            &format!("{name}: {value}"),
+            rect.center().x,
+            rect.y + (rect.h + FONT_SIZE) / 2.0 - 8.0,
+            FONT_SIZE,
+            WHITE,
+        );
+        match (previous, next) {
+            (true, _) => -1,
+            (_, true) => 1,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use macroquad::{
+    math::{Rect, Vec2},
+    window::{screen_dpi_scale, screen_height, screen_width},
+};
+
+/// Where the virtual canvas is drawn on the window, as large as it fits
+/// without changing its aspect ratio. The rest of the window is left as bars
+/// on either side.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    /// Top left corner of the canvas on the window, in logical pixels.
+    offset: Vec2,
+    /// Logical pixels per canvas pixel.
+    scale: f32,
+}
+impl Viewport {
+    /// Fits a canvas of `size` in the window. Integer scaling keeps every
+    /// canvas pixel the same number of physical pixels wide, as long as the
+    /// window is large enough to show the canvas at full size.
+    pub fn fit(size: Vec2, integer: bool) -> Self {
+        let window = Vec2 {
+            x: screen_width(),
+            y: screen_height(),
+        };
+        Self::fit_in(size, window, screen_dpi_scale(), integer)
+    }
+    /// Fits a canvas of `size` in a window of `window` logical pixels, with
+    /// `dpi` physical pixels to each of them.
+    fn fit_in(size: Vec2, window: Vec2, dpi: f32, integer: bool) -> Self {
+        // Scaled in physical pixels so that HiDPI screens get crisp edges too.
+        let window = window * dpi;
+        let mut scale = (window / size).min_element();
+        if integer && scale >= 1.0 {
+            scale = scale.floor();
+        }
+        let offset = ((window - size * scale) / 2.0).floor();
+        Self {
+            offset: offset / dpi,
+            scale: scale / dpi,
+        }
+    }
+
+    /// Area of the window the canvas of `size` is drawn in.
+    pub fn rect(&self, size: Vec2) -> Rect {
+        Rect {
+            x: self.offset.x,
+            y: self.offset.y,
+            w: size.x * self.scale,
+            h: size.y * self.scale,
+        }
+    }
+    /// Canvas point under the window point `screen`, such as the mouse.
+    pub fn canvas_from_screen(&self, screen: Vec2) -> Vec2 {
+        (screen - self.offset) / self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: Vec2 = Vec2 {
+        x: 1920.0,
+        y: 1080.0,
+    };
+
+    #[test]
+    fn exact_fit() {
+        let viewport = Viewport::fit_in(CANVAS, CANVAS, 1.0, false);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(0.0, 0.0, 1920.0, 1080.0));
+        let center = Vec2::new(960.0, 540.0);
+        assert_eq!(viewport.canvas_from_screen(center), center);
+    }
+
+    #[test]
+    fn wide_windows_get_bars_on_the_sides() {
+        let viewport = Viewport::fit_in(CANVAS, Vec2::new(2000.0, 540.0), 1.0, false);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(520.0, 0.0, 960.0, 540.0));
+        let screen = Vec2::new(520.0 + 480.0, 270.0);
+        assert_eq!(viewport.canvas_from_screen(screen), Vec2::new(960.0, 540.0));
+    }
+
+    #[test]
+    fn tall_windows_get_bars_above_and_below() {
+        let viewport = Viewport::fit_in(CANVAS, Vec2::new(960.0, 1000.0), 1.0, false);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(0.0, 230.0, 960.0, 540.0));
+        let top_left = viewport.canvas_from_screen(Vec2::new(0.0, 230.0));
+        assert_eq!(top_left, Vec2::ZERO);
+    }
+
+    #[test]
+    fn integer_scaling() {
+        let window = Vec2::new(2000.0, 1200.0);
+        let viewport = Viewport::fit_in(CANVAS, window, 1.0, true);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(40.0, 60.0, 1920.0, 1080.0));
+        // Smaller windows still shrink the canvas to fit.
+        let viewport = Viewport::fit_in(CANVAS, Vec2::new(960.0, 600.0), 1.0, true);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(0.0, 30.0, 960.0, 540.0));
+    }
+
+    #[test]
+    fn hidpi() {
+        // A 960x540 logical window is 1920x1080 physical pixels at 2x.
+        let viewport = Viewport::fit_in(CANVAS, CANVAS / 2.0, 2.0, true);
+        assert_eq!(viewport.rect(CANVAS), Rect::new(0.0, 0.0, 960.0, 540.0));
+        let screen = Vec2::new(480.0, 270.0);
+        assert_eq!(viewport.canvas_from_screen(screen), Vec2::new(960.0, 540.0));
+
+        // Bars are rounded down to whole physical pixels, 1.25 to 1 here.
+        let window = Vec2::new(961.25, 540.0);
+        let viewport = Viewport::fit_in(CANVAS, window, 2.0, true);
+        assert_eq!(viewport.rect(CANVAS).x, 0.5);
+    }
+}
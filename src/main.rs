@@ -1,6 +1,13 @@
 mod app;
 
-use app::board::sprites::{PieceMappings, SpritesMap};
+use app::board::{fen::Position, piece::PieceColor, setup, shape::Topology, variant::Variant};
+use app::{
+    Context, GameSetup,
+    clock::TimeControl,
+    scene::{Assets, SceneStack, main_menu::MainMenu},
+    theme::{ThemeSettings, Themes},
+    viewport::Viewport,
+};
 use macroquad::prelude::*;
 use tracing_subscriber::FmtSubscriber;
 
@@ -13,22 +20,80 @@ const TARGET_RESOLUTION: Vec2 = Vec2 {
 async fn main() {
     let subscriber = FmtSubscriber::new();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    rand::srand(miniquad::date::now().to_bits());
 
-    let s = std::fs::read_to_string("assets/pieces.json").unwrap();
-    let mappings: PieceMappings = serde_json::from_str(&s).unwrap();
-    let white_sprites = SpritesMap {
-        atlas: load_texture("assets/pieces.png").await.unwrap(),
-        mappings,
+    // Usage: chetro-rs [variant[@topology]] [fen|-] [shape or setup file|-] [time control]
+    let mut args = std::env::args().skip(1).peekable();
+    let started = args.peek().is_some();
+    let (variant, topology): (Variant, Option<Topology>) = match args.next() {
+        Some(arg) => {
+            let (variant, topology) = arg.split_once('@').unzip();
+            (
+                variant
+                    .unwrap_or(&arg)
+                    .parse()
+                    .expect("Invalid variant argument"),
+                topology.map(|t| t.parse().expect("Invalid topology argument")),
+            )
+        }
+        None => Default::default(),
     };
-    let black_sprites = SpritesMap {
-        atlas: load_texture("assets/pieces_black.png").await.unwrap(),
-        mappings,
+    let mut position: Option<Position> = args
+        .next()
+        .filter(|s| s != "-")
+        .map(|s| s.parse().expect("Invalid FEN argument"));
+    if let Some(path) = args.next().filter(|s| s != "-") {
+        let (shape, pieces) =
+            setup::parse(&std::fs::read_to_string(path).expect("Unreadable shape file"))
+                .expect("Invalid shape file");
+        let mut with_shape = position.unwrap_or_else(|| variant.starting_position());
+        // Setup files bring their own pieces, shape files keep the position's.
+        if !pieces.is_empty() {
+            with_shape.pieces = pieces;
+            with_shape.castling = Default::default();
+            if variant.has_castling() {
+                with_shape = with_shape.with_default_castling();
+            }
+        }
+        assert_eq!(
+            with_shape.num_cells(),
+            shape.num_cells(),
+            "The shape doesn't match the board's dimensions"
+        );
+        with_shape.pieces.retain(|p| shape.is_playable(p.position));
+        with_shape.shape = shape;
+        position = Some(with_shape);
+    }
+    if let Some(topology) = topology {
+        let mut with_topology = position.unwrap_or_else(|| variant.starting_position());
+        with_topology.shape.set_topology(topology);
+        position = Some(with_topology);
+    }
+    let time_control: Option<TimeControl> = args
+        .next()
+        .map(|s| s.parse().expect("Invalid time control argument"));
+
+    let mut themes = Themes::load(std::path::Path::new("assets/themes")).await;
+    themes.select(&ThemeSettings::load());
+    let mut assets = Assets {
+        themes,
+        move_sprite: load_texture("assets/move_gizmo.png").await.unwrap(),
     };
-    let mut game = app::Game::new(
-        white_sprites,
-        black_sprites,
-        load_texture("assets/move_gizmo.png").await.unwrap(),
-    );
+    let mut scenes = SceneStack::new(Box::new(MainMenu));
+    // A game given on the command line starts right away.
+    if started {
+        let setup = GameSetup {
+            variant,
+            position,
+            time_control,
+            color: PieceColor::White,
+        };
+        let game = app::Game::new(setup, &assets.themes, assets.move_sprite.clone());
+        scenes.push(Box::new(game));
+    }
+    let mut ctx = Context::default();
+    // Toggled with F9, for pixel-exact sprites at the cost of wider bars.
+    let mut integer_scaling = false;
 
     let render_target = render_target(TARGET_RESOLUTION.x as u32, TARGET_RESOLUTION.y as u32);
     render_target.texture.set_filter(FilterMode::Linear);
@@ -43,30 +108,28 @@ async fn main() {
         // Render to a virtual workspace
         set_camera(&render_target_cam);
         clear_background(GRAY);
-        let scale: f32 = f32::max(
-            screen_width() / TARGET_RESOLUTION.x,
-            screen_height() / TARGET_RESOLUTION.y,
-        );
+        if is_key_pressed(KeyCode::F9) {
+            integer_scaling = !integer_scaling;
+            tracing::info!("Integer scaling: {integer_scaling}");
+        }
+        let viewport = Viewport::fit(TARGET_RESOLUTION, integer_scaling);
 
-        game.ctx.mouse_position = Vec2 {
-            x: mouse_position().0 / scale,
-            y: mouse_position().1 / scale,
-        };
-        game.draw();
-        game.update();
+        ctx.mouse_position = viewport.canvas_from_screen(mouse_position().into());
+        scenes.frame(&ctx, &mut assets);
+        if scenes.is_empty() {
+            break;
+        }
 
         // Render directly to the screen
         set_default_camera();
+        let dest = viewport.rect(TARGET_RESOLUTION);
         draw_texture_ex(
             &render_target.texture,
-            0.0,
-            0.0,
+            dest.x,
+            dest.y,
             WHITE,
             DrawTextureParams {
-                dest_size: Some(vec2(
-                    TARGET_RESOLUTION.x * scale,
-                    TARGET_RESOLUTION.y * scale,
-                )),
+                dest_size: Some(dest.size()),
                 flip_y: true,
                 ..Default::default()
             },